@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+
+use crate::{parse_tiles, start_shape, walk, Loop, Point, Tile};
+
+/// Measures of a loop through lattice points.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Area {
+    /// twice the shoelace area, so it stays an integer
+    pub double_area: u128,
+    /// lattice points on the loop
+    pub boundary: u128,
+    /// lattice points strictly inside, by Pick's theorem
+    pub interior: u128,
+}
+
+/// Shoelace formula and Pick's theorem over the ordered vertices of a closed loop
+/// whose edges are horizontal or vertical. Consecutive vertices may be any distance apart.
+pub fn pick(vertices: &[Point]) -> Area {
+    let mut double_area = 0i128;
+    let mut boundary = 0u128;
+    for (i, a) in vertices.iter().enumerate() {
+        let b = vertices[(i + 1) % vertices.len()];
+        let (ax, ay, bx, by) = (a.x as i128, a.y as i128, b.x as i128, b.y as i128);
+        double_area += ax * by - bx * ay;
+        boundary += ax.abs_diff(bx) + ay.abs_diff(by);
+    }
+    let double_area = double_area.unsigned_abs();
+
+    // A = i + b/2 - 1
+    let interior = (double_area + 2).saturating_sub(boundary) / 2;
+    Area {
+        double_area,
+        boundary,
+        interior,
+    }
+}
+
+impl Loop {
+    pub fn area(&self) -> Area {
+        pick(&self.tiles)
+    }
+
+    /// Enclosed tile count, asserting the row scan and Pick's theorem agree.
+    pub fn cross_check(&self) -> usize {
+        let interior = self.area().interior;
+        assert_eq!(
+            interior,
+            self.enclosed.len() as u128,
+            "Pick's theorem disagrees with the row scan"
+        );
+        self.enclosed.len()
+    }
+}
+
+/// Pipes by position with no storage for ground, for maps too large or too empty for a grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseMap {
+    pub pipes: HashMap<Point, Tile>,
+    pub start: Point,
+}
+
+impl SparseMap {
+    pub fn parse(map: &str) -> Self {
+        let mut pipes = HashMap::new();
+        let start = parse_tiles(map, |point, tile| {
+            if tile != Tile::Ground {
+                pipes.insert(point, tile);
+            }
+        });
+        Self::new(pipes, start)
+    }
+
+    /// Whatever is at `start` is replaced by the pipe shape that closes the loop.
+    pub fn new(mut pipes: HashMap<Point, Tile>, start: Point) -> Self {
+        pipes.remove(&start);
+        let shape =
+            start_shape(&pipes, start).unwrap_or_else(|| panic!("S at {start:?} is not on a loop"));
+        pipes.insert(start, Tile::Pipe(shape));
+        Self { pipes, start }
+    }
+
+    /// Corners of the loop through the start in walking order.
+    pub fn corners(&self) -> Vec<Point> {
+        let Tile::Pipe([first, _]) = self.pipes[&self.start] else {
+            unreachable!()
+        };
+        let mut corners = Vec::new();
+        let (mut point, mut dir) = (self.start, first);
+        loop {
+            let (next, next_dir) = walk(&self.pipes, point, dir).expect("the loop is broken");
+            if next_dir != dir {
+                corners.push(next);
+            }
+            (point, dir) = (next, next_dir);
+            if point == self.start {
+                return corners;
+            }
+        }
+    }
+
+    pub fn area(&self) -> Area {
+        pick(&self.corners())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{pick, SparseMap};
+    use crate::{parse_map, Dir, Loop, Point, Tile};
+
+    const MAPS: &[&str] = &[
+        concat! {
+            ".....\n",
+            ".S-7.\n",
+            ".|.|.\n",
+            ".L-J.\n",
+            ".....\n"
+        },
+        concat! {
+            "..F7.\n",
+            ".FJ|.\n",
+            "SJ.L7\n",
+            "|F--J\n",
+            "LJ...\n"
+        },
+        concat! {
+            ".F----7F7F7F7F-7....\n",
+            ".|F--7||||||||FJ....\n",
+            ".||.FJ||||||||L7....\n",
+            "FJL7L7LJLJ||LJ.L-7..\n",
+            "L--J.L7...LJS7F-7L7.\n",
+            "....F-J..F7FJ|L7L7L7\n",
+            "....L7.F7||L7|.L7L7|\n",
+            ".....|FJLJ|FJ|F7|.LJ\n",
+            "....FJL-7.||.||||...\n",
+            "....L---J.LJ.LJLJ...\n",
+        },
+        concat! {
+            "FF7FSF7F7F7F7F7F---7\n",
+            "L|LJ||||||||||||F--J\n",
+            "FL-7LJLJ||||||LJL-77\n",
+            "F--JF--7||LJLJ7F7FJ-\n",
+            "L---JF-JLJ.||-FJLJJ7\n",
+            "|F|F-JF---7F7-L7L|7|\n",
+            "|FFJF7L7F-JF7|JL---7\n",
+            "7-L-JL7||F7|L7F-7F7|\n",
+            "L.L7LFJ|||||FJL7||LJ\n",
+            "L7JLJL-JLJLJL--JLJ.L\n",
+        },
+    ];
+
+    #[test]
+    fn pick_matches_row_scan() {
+        for map in MAPS {
+            let (tiles, start) = parse_map(map);
+            let main_loop = Loop::find(&tiles, start);
+            main_loop.cross_check();
+            assert_eq!(main_loop.area().boundary, main_loop.len() as u128);
+            assert_eq!(SparseMap::parse(map).area(), main_loop.area());
+        }
+    }
+
+    #[test]
+    fn huge_rectangle() {
+        let side = 3_000_000_000;
+        let corners = [
+            Point::new(0, 0),
+            Point::new(side, 0),
+            Point::new(side, side),
+            Point::new(0, side),
+        ];
+        let area = pick(&corners);
+        assert_eq!(area.double_area, 2 * side as u128 * side as u128);
+        assert_eq!(area.boundary, 4 * side as u128);
+        assert_eq!(area.interior, (side as u128 - 1).pow(2));
+    }
+
+    #[test]
+    fn sparse_map() {
+        // a square loop far from the origin, with nothing stored for the ground around it
+        let (x, y, side) = (1_000_000_000, 2_000_000_000, 1000);
+        let mut pipes = HashMap::new();
+        for i in 1..side {
+            pipes.insert(Point::new(x + i, y), Tile::Pipe([Dir::West, Dir::East]));
+            pipes.insert(
+                Point::new(x + i, y + side),
+                Tile::Pipe([Dir::West, Dir::East]),
+            );
+            pipes.insert(Point::new(x, y + i), Tile::Pipe([Dir::North, Dir::South]));
+            pipes.insert(
+                Point::new(x + side, y + i),
+                Tile::Pipe([Dir::North, Dir::South]),
+            );
+        }
+        pipes.insert(Point::new(x + side, y), Tile::Pipe([Dir::West, Dir::South]));
+        pipes.insert(
+            Point::new(x + side, y + side),
+            Tile::Pipe([Dir::North, Dir::West]),
+        );
+        pipes.insert(Point::new(x, y + side), Tile::Pipe([Dir::North, Dir::East]));
+
+        let map = SparseMap::new(pipes, Point::new(x, y));
+        assert_eq!(map.pipes[&map.start], Tile::Pipe([Dir::East, Dir::South]));
+        assert_eq!(map.corners().len(), 4);
+        assert_eq!(map.area().interior, (side as u128 - 1).pow(2));
+    }
+}
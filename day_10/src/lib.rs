@@ -0,0 +1,373 @@
+use std::collections::HashMap;
+
+mod area;
+mod loops;
+
+pub use area::{pick, Area, SparseMap};
+pub use loops::Loops;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Dir {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Dir {
+    pub const ALL: [Dir; 4] = [Dir::North, Dir::East, Dir::South, Dir::West];
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Dir::North => Dir::South,
+            Dir::East => Dir::West,
+            Dir::South => Dir::North,
+            Dir::West => Dir::East,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Tile {
+    Pipe([Dir; 2]),
+    Ground,
+}
+
+impl Tile {
+    pub fn connects(self, dir: Dir) -> bool {
+        matches!(self, Tile::Pipe(cons) if cons.contains(&dir))
+    }
+
+    /// Where a pipe entered from `from` leads.
+    pub fn exit(self, from: Dir) -> Option<Dir> {
+        match self {
+            Tile::Pipe([a, b]) if a == from => Some(b),
+            Tile::Pipe([a, b]) if b == from => Some(a),
+            _ => None,
+        }
+    }
+
+    pub fn box_drawing(self) -> char {
+        match self {
+            Tile::Pipe(cons) => match cons {
+                [Dir::North, Dir::South] | [Dir::South, Dir::North] => '│',
+                [Dir::West, Dir::East] | [Dir::East, Dir::West] => '─',
+                [Dir::North, Dir::East] | [Dir::East, Dir::North] => '└',
+                [Dir::North, Dir::West] | [Dir::West, Dir::North] => '┘',
+                [Dir::West, Dir::South] | [Dir::South, Dir::West] => '┐',
+                [Dir::East, Dir::South] | [Dir::South, Dir::East] => '┌',
+                cons => panic!("{cons:?}"),
+            },
+            Tile::Ground => ' ',
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Point {
+    pub x: u32,
+    pub y: u32,
+}
+
+impl Point {
+    pub fn new(x: u32, y: u32) -> Self {
+        Self { x, y }
+    }
+
+    /// `None` off the top or left edge.
+    pub fn to(self, dir: Dir) -> Option<Self> {
+        Some(match dir {
+            Dir::North => Point::new(self.x, self.y.checked_sub(1)?),
+            Dir::East => Point::new(self.x + 1, self.y),
+            Dir::South => Point::new(self.x, self.y + 1),
+            Dir::West => Point::new(self.x.checked_sub(1)?, self.y),
+        })
+    }
+}
+
+/// Tiles and the position of `S`, whose tile is the pipe shape that closes the loop.
+pub fn parse_map(map: &str) -> (Vec<Vec<Tile>>, Point) {
+    let mut tiles: Vec<Vec<Tile>> = Vec::new();
+    let starting_point = parse_tiles(map, |point, tile| {
+        // blank lines have no tiles but still count as rows
+        let y = point.y as usize;
+        if tiles.len() <= y {
+            tiles.resize_with(y + 1, Vec::new);
+        }
+        tiles[y].push(tile);
+    });
+
+    let shape = start_shape(tiles.as_slice(), starting_point)
+        .unwrap_or_else(|| panic!("S at {starting_point:?} is not on a loop"));
+    tiles[starting_point.y as usize][starting_point.x as usize] = Tile::Pipe(shape);
+    (tiles, starting_point)
+}
+
+/// Feeds every tile to `f` row by row, `S` as ground, and returns the position of `S`.
+fn parse_tiles(map: &str, mut f: impl FnMut(Point, Tile)) -> Point {
+    let mut starting_point: Option<Point> = None;
+    for (y, line) in map.lines().enumerate() {
+        for (x, c) in line.char_indices() {
+            let tile = match c {
+                '|' => Tile::Pipe([Dir::North, Dir::South]),
+                '-' => Tile::Pipe([Dir::West, Dir::East]),
+                'L' => Tile::Pipe([Dir::North, Dir::East]),
+                'J' => Tile::Pipe([Dir::North, Dir::West]),
+                '7' => Tile::Pipe([Dir::West, Dir::South]),
+                'F' => Tile::Pipe([Dir::East, Dir::South]),
+                '.' => Tile::Ground,
+                'S' => {
+                    starting_point = Some(Point::new(x as _, y as _));
+                    Tile::Ground
+                }
+                c => panic!("invalid pixel {c:?}"),
+            };
+            f(Point::new(x as _, y as _), tile);
+        }
+    }
+    starting_point.expect("no S in the map")
+}
+
+/// Dense grids and sparse maps alike.
+trait Tiles {
+    fn tile(&self, point: Point) -> Option<Tile>;
+}
+
+impl Tiles for [Vec<Tile>] {
+    fn tile(&self, point: Point) -> Option<Tile> {
+        self.get(point.y as usize)?.get(point.x as usize).copied()
+    }
+}
+
+impl Tiles for HashMap<Point, Tile> {
+    fn tile(&self, point: Point) -> Option<Tile> {
+        self.get(&point).copied()
+    }
+}
+
+/// Moves from `point` towards `dir` and follows the pipe there.
+fn walk<T: Tiles + ?Sized>(map: &T, point: Point, dir: Dir) -> Option<(Point, Dir)> {
+    let point = point.to(dir)?;
+    let to_dir = map.tile(point)?.exit(dir.opposite())?;
+    Some((point, to_dir))
+}
+
+/// The two sides of `start` a walk leaves through and comes back in by.
+/// Neighbours that only lead to dead ends are ignored.
+fn start_shape<T: Tiles + ?Sized>(map: &T, start: Point) -> Option<[Dir; 2]> {
+    Dir::ALL.into_iter().find_map(|first| {
+        // pipes have two ends, so the walk cannot revisit a tile before returning to S
+        let (mut point, mut dir) = walk(map, start, first)?;
+        while point.to(dir) != Some(start) {
+            (point, dir) = walk(map, point, dir)?;
+        }
+        let last = dir.opposite();
+        (last != first).then_some([first, last])
+    })
+}
+
+/// A closed loop of pipes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Loop {
+    /// in walking order, starting at `S` for the loop through it
+    pub tiles: Vec<Point>,
+    /// tiles inside the loop, row by row
+    pub enclosed: Vec<Point>,
+    /// top left of the bounding box, the only part of the map `on_loop` and `inside` cover
+    corner: Point,
+    on_loop: Vec<Vec<bool>>,
+    inside: Vec<Vec<bool>>,
+}
+
+impl Loop {
+    /// The loop through `start`.
+    pub fn find(map: &[Vec<Tile>], start: Point) -> Self {
+        let Some(Tile::Pipe([first, _])) = map.tile(start) else {
+            panic!("{start:?} is not a pipe");
+        };
+        let mut tiles = vec![start];
+        let (mut point, mut dir) = walk(map, start, first).expect("the loop is broken");
+        while point != start {
+            tiles.push(point);
+            (point, dir) = walk(map, point, dir).expect("the loop is broken");
+        }
+        Self::from_tiles(map, tiles)
+    }
+
+    /// `tiles` in walking order, at least one.
+    fn from_tiles(map: &[Vec<Tile>], tiles: Vec<Point>) -> Self {
+        let corner = Point::new(
+            tiles.iter().map(|point| point.x).min().unwrap(),
+            tiles.iter().map(|point| point.y).min().unwrap(),
+        );
+        let width = tiles.iter().map(|point| point.x).max().unwrap() - corner.x + 1;
+        let height = tiles.iter().map(|point| point.y).max().unwrap() - corner.y + 1;
+        let mut on_loop = vec![vec![false; width as usize]; height as usize];
+        for point in &tiles {
+            on_loop[(point.y - corner.y) as usize][(point.x - corner.x) as usize] = true;
+        }
+
+        // a ray along the row crosses the loop at every loop pipe leading south
+        let mut enclosed = Vec::new();
+        let mut inside = vec![vec![false; width as usize]; height as usize];
+        for (dy, row) in on_loop.iter().enumerate() {
+            let y = corner.y as usize + dy;
+            let mut is_inside_loop = false;
+            for (dx, &is_on_loop) in row.iter().enumerate() {
+                let x = corner.x as usize + dx;
+                if is_on_loop {
+                    if map[y][x].connects(Dir::South) {
+                        is_inside_loop = !is_inside_loop;
+                    }
+                } else if is_inside_loop {
+                    inside[dy][dx] = true;
+                    enclosed.push(Point::new(x as _, y as _));
+                }
+            }
+        }
+
+        Self {
+            tiles,
+            enclosed,
+            corner,
+            on_loop,
+            inside,
+        }
+    }
+
+    /// Row and column of `point` within the bounding box, `None` outside it.
+    fn local(&self, point: Point) -> Option<(usize, usize)> {
+        let y = point.y.checked_sub(self.corner.y)? as usize;
+        let x = point.x.checked_sub(self.corner.x)? as usize;
+        (y < self.on_loop.len() && x < self.on_loop[y].len()).then_some((y, x))
+    }
+
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// Steps to the tile farthest from `S` along the loop.
+    pub fn farthest(&self) -> usize {
+        self.len() / 2
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        self.local(point).is_some_and(|(y, x)| self.on_loop[y][x])
+    }
+
+    pub fn is_inside(&self, point: Point) -> bool {
+        self.local(point).is_some_and(|(y, x)| self.inside[y][x])
+    }
+
+    /// The loop in box-drawing characters, other tiles marked `I` inside and `O` outside.
+    pub fn render(&self, map: &[Vec<Tile>]) -> String {
+        let mut out = String::new();
+        for (y, row) in map.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                let point = Point::new(x as _, y as _);
+                out.push(match () {
+                    _ if self.contains(point) => tile.box_drawing(),
+                    _ if self.is_inside(point) => 'I',
+                    _ => 'O',
+                });
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_map, Dir, Loop, Point, Tile};
+
+    #[test]
+    fn start_shape() {
+        let map = concat! {
+            ".....\n",
+            "-S-7.\n",
+            ".|.|.\n",
+            ".L-J.\n",
+            ".....\n"
+        };
+        let (map, start) = parse_map(map);
+        assert_eq!(start, Point::new(1, 1));
+        assert_eq!(map[1][1], Tile::Pipe([Dir::East, Dir::South]));
+
+        let map = concat! {
+            "..F7.\n",
+            ".FJ|.\n",
+            "SJ.L7\n",
+            "|F--J\n",
+            "LJ...\n"
+        };
+        let (map, start) = parse_map(map);
+        assert!(map[start.y as usize][start.x as usize].connects(Dir::East));
+        assert!(map[start.y as usize][start.x as usize].connects(Dir::South));
+    }
+
+    #[test]
+    fn start_without_south() {
+        let map = concat! {
+            "...........\n",
+            ".F--7.F--7.\n",
+            ".|..|.|..|.\n",
+            ".|F-J.L-7|.\n",
+            ".||.....||.\n",
+            ".||.....||.\n",
+            ".|L-----J|.\n",
+            ".S-------J.\n",
+            "...........\n",
+        };
+        let (map, start) = parse_map(map);
+        let main_loop = Loop::find(&map, start);
+        assert_eq!(main_loop.enclosed.len(), 4);
+        assert!(!main_loop.is_inside(Point::new(10, 7)));
+    }
+
+    #[test]
+    fn blank_line() {
+        let map = concat! {
+            ".....\n",
+            "\n",
+            ".S-7.\n",
+            ".|.|.\n",
+            ".L-J.\n",
+        };
+        let (map, start) = parse_map(map);
+        assert_eq!(start, Point::new(1, 2));
+        assert!(map[1].is_empty());
+        let pipe_loop = Loop::find(&map, start);
+        assert_eq!(pipe_loop.len(), 8);
+        assert_eq!(pipe_loop.enclosed, [Point::new(2, 3)]);
+    }
+
+    #[test]
+    fn render() {
+        let map = concat! {
+            ".....\n",
+            ".S-7.\n",
+            ".|.|.\n",
+            ".L-J.\n",
+            ".....\n"
+        };
+        let (map, start) = parse_map(map);
+        let main_loop = Loop::find(&map, start);
+        assert_eq!(main_loop.len(), 8);
+        assert_eq!(main_loop.tiles[0], start);
+        assert_eq!(
+            main_loop.render(&map),
+            concat! {
+                "OOOOO\n",
+                "O┌─┐O\n",
+                "O│I│O\n",
+                "O└─┘O\n",
+                "OOOOO\n"
+            }
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use crate::{walk, Loop, Point, Tile};
+
+const COLORS: &[&str] = &["31", "32", "34", "35", "36"];
+const MAIN_COLOR: &str = "1;33";
+
+/// Every closed loop of pipes in a map. Each pipe connects to at most two
+/// others, so pipes not on a loop form open paths.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Loops {
+    /// in order of their first tile, row by row
+    pub loops: Vec<Loop>,
+    /// index of the loop through the start, `None` if it is not on one
+    pub main: Option<usize>,
+}
+
+impl Loops {
+    pub fn find(map: &[Vec<Tile>], start: Point) -> Self {
+        let mut visited = map
+            .iter()
+            .map(|row| vec![false; row.len()])
+            .collect::<Vec<_>>();
+        let mut loops = Vec::new();
+        for (y, row) in map.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                let Tile::Pipe([first, _]) = *tile else {
+                    continue;
+                };
+                if visited[y][x] {
+                    continue;
+                }
+                visited[y][x] = true;
+
+                // a failed walk means the pipe is on an open path, and so is every pipe walked
+                let origin = Point::new(x as _, y as _);
+                let mut tiles = vec![origin];
+                let mut next = walk(map, origin, first);
+                while let Some((point, dir)) = next {
+                    if point == origin {
+                        break;
+                    }
+                    visited[point.y as usize][point.x as usize] = true;
+                    tiles.push(point);
+                    next = walk(map, point, dir);
+                }
+                if next.is_some() {
+                    loops.push(Loop::from_tiles(map, tiles));
+                }
+            }
+        }
+
+        let main = loops.iter().position(|pipe_loop| pipe_loop.contains(start));
+        Self { loops, main }
+    }
+
+    pub fn main_loop(&self) -> Option<&Loop> {
+        self.loops.get(self.main?)
+    }
+
+    /// Every loop in box-drawing characters with its own ANSI colour, the main one
+    /// in bold yellow, everything else as a dot.
+    pub fn render(&self, map: &[Vec<Tile>]) -> String {
+        let mut colors = map
+            .iter()
+            .map(|row| vec![None; row.len()])
+            .collect::<Vec<_>>();
+        for (i, pipe_loop) in self.loops.iter().enumerate() {
+            let color = match self.main {
+                Some(main) if main == i => MAIN_COLOR,
+                Some(main) if main < i => COLORS[(i - 1) % COLORS.len()],
+                _ => COLORS[i % COLORS.len()],
+            };
+            for point in &pipe_loop.tiles {
+                colors[point.y as usize][point.x as usize] = Some(color);
+            }
+        }
+
+        let mut out = String::new();
+        for (y, row) in map.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                match colors[y][x] {
+                    Some(color) => {
+                        out.push_str(&format!("\x1b[{color}m{}\x1b[0m", tile.box_drawing()))
+                    }
+                    None => out.push('.'),
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Loops;
+    use crate::{parse_map, Loop, Point};
+
+    const INPUT: &str = concat! {
+        "S-7F7\n",
+        "|.|LJ\n",
+        "L-J..\n",
+        "F-.F7\n",
+    };
+
+    #[test]
+    fn find() {
+        let (map, start) = parse_map(INPUT);
+        let loops = Loops::find(&map, start);
+        assert_eq!(loops.loops.len(), 2);
+        assert_eq!(loops.main, Some(0));
+        assert_eq!(loops.main_loop(), Some(&Loop::find(&map, start)));
+
+        let small = &loops.loops[1];
+        assert_eq!(small.len(), 4);
+        assert_eq!(small.tiles[0], Point::new(3, 0));
+        assert!(small.enclosed.is_empty());
+        assert_eq!(loops.main_loop().unwrap().enclosed, [Point::new(1, 1)]);
+    }
+
+    #[test]
+    fn many_small_loops() {
+        // each loop only keeps its own 2 by 2 corner of the map
+        let side = 200;
+        let mut map = String::new();
+        for y in 0..side {
+            let row = if y % 2 == 0 { "F7" } else { "LJ" };
+            map.push_str(&row.repeat(side / 2));
+            map.push('\n');
+        }
+        map.replace_range(0..1, "S");
+        let (map, start) = parse_map(&map);
+        let loops = Loops::find(&map, start);
+        assert_eq!(loops.loops.len(), side * side / 4);
+        assert_eq!(loops.main, Some(0));
+        for pipe_loop in &loops.loops {
+            assert_eq!(pipe_loop.on_loop.len(), 2);
+            assert!(pipe_loop.enclosed.is_empty());
+        }
+        let last = loops.loops.last().unwrap();
+        assert!(last.contains(Point::new(side as u32 - 1, side as u32 - 1)));
+        assert!(!last.contains(Point::new(0, 0)));
+    }
+
+    #[test]
+    fn render() {
+        let (map, start) = parse_map(INPUT);
+        let render = Loops::find(&map, start).render(&map);
+        assert!(render.starts_with("\x1b[1;33m┌\x1b[0m"));
+        assert!(render.contains("\x1b[31m┐\x1b[0m"));
+
+        let plain = render
+            .split("\x1b[")
+            .map(|part| part.split_once('m').map_or(part, |(_, rest)| rest))
+            .collect::<String>();
+        assert_eq!(
+            plain,
+            concat! {
+                "┌─┐┌┐\n",
+                "│.│└┘\n",
+                "└─┘..\n",
+                ".....\n",
+            }
+        );
+    }
+}
@@ -1,174 +1,32 @@
+use day_10::{parse_map, Loop, Loops};
+
 const INPUT: &str = include_str!("input.txt");
 
 fn main() {
     println!("Farthest: {}", farthest(INPUT));
     println!("Enclosed: {}", enclosed(INPUT));
-}
-
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
-enum Dir {
-    North,
-    East,
-    South,
-    West,
-}
-
-impl Dir {
-    fn opposite(self) -> Self {
-        match self {
-            Dir::North => Dir::South,
-            Dir::East => Dir::West,
-            Dir::South => Dir::North,
-            Dir::West => Dir::East,
-        }
-    }
-}
-
-enum Tile {
-    Pipe([Dir; 2], bool),
-    Start,
-    Ground,
-}
-
-#[derive(Clone, Copy)]
-struct Point {
-    x: u32,
-    y: u32,
-}
-
-impl Point {
-    fn new(x: u32, y: u32) -> Self {
-        Self { x, y }
-    }
-
-    fn to(self, dir: Dir) -> Self {
-        match dir {
-            Dir::North => Point::new(self.x, self.y.saturating_sub(1)),
-            Dir::East => Point::new(self.x + 1, self.y),
-            Dir::South => Point::new(self.x, self.y + 1),
-            Dir::West => Point::new(self.x.saturating_sub(1), self.y),
-        }
-    }
-}
-
-fn farthest(map: &str) -> u32 {
-    let (mut map, starting_point) = parse_map(map);
-    let mut cur = [Dir::North, Dir::East, Dir::South, Dir::West]
-        .into_iter()
-        .find_map(|dir| walk(&mut map, starting_point, dir))
-        .unwrap();
-    let mut distance = 1;
-
-    while let Some(next) = walk(&mut map, cur.0, cur.1) {
-        cur = next;
-        distance += 1;
-    }
-    (distance + 1) / 2
-}
-
-fn enclosed(map: &str) -> u32 {
-    let (mut map, starting_point) = parse_map(map);
-    let mut cur = [Dir::North, Dir::East, Dir::South, Dir::West]
-        .into_iter()
-        .find_map(|dir| walk(&mut map, starting_point, dir))
-        .unwrap();
-    while let Some(next) = walk(&mut map, cur.0, cur.1) {
-        cur = next;
-    }
 
-    // eprintln!("----------------");
-    let mut enclosed = 0;
-    for row in map {
-        let mut is_inside_loop = false;
-        for tile in row {
-            match tile {
-                Tile::Start => {
-                    is_inside_loop = !is_inside_loop;
-                    // if is_inside_loop {
-                    //     eprint!("|");
-                    // } else {
-                    //     eprint!(":")
-                    // }
-                }
-                Tile::Pipe(cons, true) => {
-                    if cons.contains(&Dir::South) {
-                        is_inside_loop = !is_inside_loop;
-                    }
-                    // let c = match cons {
-                    //     [Dir::East, Dir::North] | [Dir::North, Dir::East] => 'L',
-                    //     [Dir::South, Dir::North] | [Dir::North, Dir::South] => '|',
-                    //     [Dir::West, Dir::North] | [Dir::North, Dir::West] => 'J',
-                    //     [Dir::South, Dir::East] | [Dir::East, Dir::South] => 'F',
-                    //     [Dir::West, Dir::East] | [Dir::East, Dir::West] => '-',
-                    //     [Dir::West, Dir::South] | [Dir::South, Dir::West] => '7',
-                    //     cons => panic!("{cons:?}"),
-                    // };
-                    // eprint!("{c}");
-                }
-                Tile::Pipe(_, false) | Tile::Ground => {
-                    if is_inside_loop {
-                        enclosed += 1;
-                        // eprint!("I");
-                    }
-                    // else {
-                    //     eprint!("O");
-                    // }
-                }
-            }
-        }
-        // eprintln!();
+    let (map, starting_point) = parse_map(INPUT);
+    let loops = Loops::find(&map, starting_point);
+    println!("Loops: {}", loops.loops.len());
+    for (i, pipe_loop) in loops.loops.iter().enumerate() {
+        let main = if loops.main == Some(i) { " (main)" } else { "" };
+        println!(
+            "  {} tiles enclosing {}{main}",
+            pipe_loop.len(),
+            pipe_loop.area().interior
+        );
     }
-    enclosed
 }
 
-fn parse_map(map: &str) -> (Vec<Vec<Tile>>, Point) {
-    let mut starting_point: Option<Point> = None;
-
-    let map = map
-        .lines()
-        .enumerate()
-        .map(|(y, line)| {
-            line.char_indices()
-                .map(|(x, c)| match c {
-                    '|' => Tile::Pipe([Dir::North, Dir::South], false),
-                    '-' => Tile::Pipe([Dir::West, Dir::East], false),
-                    'L' => Tile::Pipe([Dir::North, Dir::East], false),
-                    'J' => Tile::Pipe([Dir::North, Dir::West], false),
-                    '7' => Tile::Pipe([Dir::West, Dir::South], false),
-                    'F' => Tile::Pipe([Dir::East, Dir::South], false),
-                    '.' => Tile::Ground,
-                    'S' => {
-                        starting_point = Some(Point::new(x as _, y as _));
-                        Tile::Start
-                    }
-                    c => panic!("invalid pixel {c:?}"),
-                })
-                .collect::<Vec<_>>()
-        })
-        .collect::<Vec<_>>();
-    (map, starting_point.unwrap())
+fn farthest(map: &str) -> usize {
+    let (map, starting_point) = parse_map(map);
+    Loop::find(&map, starting_point).farthest()
 }
 
-fn walk(map: &mut [Vec<Tile>], point: Point, dir: Dir) -> Option<(Point, Dir)> {
-    let point = point.to(dir);
-    let tile = map.get_mut(point.y as usize)?.get_mut(point.x as usize)?;
-    let from_dir = dir.opposite();
-    let to_dir = match tile {
-        Tile::Pipe(cons, is_main_loop) => {
-            if cons[0] == from_dir {
-                *is_main_loop = true;
-                cons[1]
-            } else if cons[1] == from_dir {
-                *is_main_loop = true;
-                cons[0]
-            } else {
-                return None;
-            }
-        }
-        Tile::Ground | Tile::Start => return None,
-    };
-
-    Some((point, to_dir))
+fn enclosed(map: &str) -> usize {
+    let (map, starting_point) = parse_map(map);
+    Loop::find(&map, starting_point).cross_check()
 }
 
 #[cfg(test)]
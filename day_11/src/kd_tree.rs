@@ -0,0 +1,165 @@
+use std::collections::BinaryHeap;
+
+use crate::{Metric, Point};
+
+/// 2-d tree over galaxy positions, stored implicitly: the node for `order[lo..hi]` is
+/// its median, split on y at even depths and x at odd ones, with the halves either side
+/// as its children.
+#[derive(Debug, Clone)]
+pub(crate) struct KdTree {
+    points: Vec<Point>,
+    /// galaxy indices in tree order
+    order: Vec<usize>,
+    /// corners of the bounding box of the subtree rooted at each position of `order`
+    bounds: Vec<(Point, Point)>,
+}
+
+fn coord(point: Point, depth: usize) -> usize {
+    if depth.is_multiple_of(2) {
+        point.y
+    } else {
+        point.x
+    }
+}
+
+impl KdTree {
+    pub fn new(points: Vec<Point>) -> Self {
+        let mut tree = Self {
+            order: (0..points.len()).collect(),
+            bounds: vec![(Point { y: 0, x: 0 }, Point { y: 0, x: 0 }); points.len()],
+            points,
+        };
+        tree.build(0, tree.points.len(), 0);
+        tree
+    }
+
+    fn build(&mut self, lo: usize, hi: usize, depth: usize) {
+        if lo >= hi {
+            return;
+        }
+        let points = &self.points;
+        let mid = lo + (hi - lo) / 2;
+        self.order[lo..hi].select_nth_unstable_by_key(mid - lo, |&i| coord(points[i], depth));
+
+        let subtree = self.order[lo..hi].iter().map(|&i| points[i]);
+        let min = subtree.clone().fold(
+            Point {
+                y: usize::MAX,
+                x: usize::MAX,
+            },
+            |min, p| Point {
+                y: min.y.min(p.y),
+                x: min.x.min(p.x),
+            },
+        );
+        let max = subtree.fold(Point { y: 0, x: 0 }, |max, p| Point {
+            y: max.y.max(p.y),
+            x: max.x.max(p.x),
+        });
+        self.bounds[mid] = (min, max);
+
+        self.build(lo, mid, depth + 1);
+        self.build(mid + 1, hi, depth + 1);
+    }
+
+    /// The `k` galaxies closest to galaxy `query`, excluding itself, closest first.
+    /// Ties go to the lower index.
+    pub fn nearest(&self, query: usize, k: usize, metric: Metric) -> Vec<(u128, usize)> {
+        let point = self.points[query];
+        self.best(
+            query,
+            k,
+            |other| metric.distance(point, other),
+            |bounds| metric.range(point, bounds).0,
+        )
+    }
+
+    /// The `k` galaxies farthest from galaxy `query`, farthest first.
+    /// Ties go to the lower index.
+    pub fn farthest(&self, query: usize, k: usize, metric: Metric) -> Vec<(u128, usize)> {
+        let point = self.points[query];
+        self.best(
+            query,
+            k,
+            |other| u128::MAX - metric.distance(point, other),
+            |bounds| u128::MAX - metric.range(point, bounds).1,
+        )
+        .into_iter()
+        .map(|(score, i)| (u128::MAX - score, i))
+        .collect()
+    }
+
+    /// The `k` galaxies other than `query` with the lowest `(score, index)`, best first.
+    /// `bound` is a lower bound on the score of anything inside a bounding box.
+    fn best(
+        &self,
+        query: usize,
+        k: usize,
+        score: impl Fn(Point) -> u128,
+        bound: impl Fn((Point, Point)) -> u128,
+    ) -> Vec<(u128, usize)> {
+        struct Search<'t, S, B> {
+            tree: &'t KdTree,
+            query: usize,
+            k: usize,
+            score: S,
+            bound: B,
+            /// worst of the best so far on top
+            heap: BinaryHeap<(u128, usize)>,
+        }
+
+        impl<S: Fn(Point) -> u128, B: Fn((Point, Point)) -> u128> Search<'_, S, B> {
+            fn visit(&mut self, lo: usize, hi: usize) {
+                if lo >= hi {
+                    return;
+                }
+                let mid = lo + (hi - lo) / 2;
+                let full = self.heap.len() == self.k;
+                if full
+                    && self
+                        .heap
+                        .peek()
+                        .is_some_and(|&(worst, _)| (self.bound)(self.tree.bounds[mid]) > worst)
+                {
+                    return;
+                }
+
+                let i = self.tree.order[mid];
+                if i != self.query {
+                    self.heap.push(((self.score)(self.tree.points[i]), i));
+                    if self.heap.len() > self.k {
+                        self.heap.pop();
+                    }
+                }
+
+                // the more promising side first, so the other is more likely to be pruned
+                let left = (lo, mid);
+                let right = (mid + 1, hi);
+                let bound = |(lo, hi): (usize, usize)| {
+                    (lo < hi).then(|| (self.bound)(self.tree.bounds[lo + (hi - lo) / 2]))
+                };
+                let (first, second) = if bound(left) <= bound(right) {
+                    (left, right)
+                } else {
+                    (right, left)
+                };
+                self.visit(first.0, first.1);
+                self.visit(second.0, second.1);
+            }
+        }
+
+        if k == 0 {
+            return Vec::new();
+        }
+        let mut search = Search {
+            tree: self,
+            query,
+            k,
+            score,
+            bound,
+            heap: BinaryHeap::with_capacity(k + 1),
+        };
+        search.visit(0, self.points.len());
+        search.heap.into_sorted_vec()
+    }
+}
@@ -0,0 +1,341 @@
+use std::collections::HashMap;
+
+mod kd_tree;
+mod universe;
+
+pub use universe::{Metric, Universe};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Point {
+    pub y: usize,
+    pub x: usize,
+}
+
+/// Width of every empty row and column once the universe has expanded.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Expansion {
+    /// width of an empty row or column without its own entry
+    pub default: usize,
+    pub rows: HashMap<usize, usize>,
+    pub columns: HashMap<usize, usize>,
+}
+
+impl Expansion {
+    pub fn uniform(width: usize) -> Self {
+        Self {
+            default: width,
+            ..Self::default()
+        }
+    }
+
+    /// One `default <width>`, `row <y> <width>` or `column <x> <width>` per line.
+    /// Empty lines are 2 wide unless a default is given. Entries for rows or columns with
+    /// galaxies are ignored.
+    pub fn parse(side: &str) -> Self {
+        let mut expansion = Self::uniform(2);
+        for line in side.lines().filter(|line| !line.trim().is_empty()) {
+            let numbers = line
+                .split_whitespace()
+                .skip(1)
+                .map(|n| {
+                    n.parse::<usize>()
+                        .unwrap_or_else(|_| panic!("{line:?}: {n:?} is not a number"))
+                })
+                .collect::<Vec<_>>();
+            match (line.split_whitespace().next(), &numbers[..]) {
+                (Some("default"), &[width]) => expansion.default = width,
+                (Some("row"), &[y, width]) => {
+                    expansion.rows.insert(y, width);
+                }
+                (Some("column"), &[x, width]) => {
+                    expansion.columns.insert(x, width);
+                }
+                _ => panic!("invalid expansion {line:?}"),
+            }
+        }
+        expansion
+    }
+
+    pub fn row(&self, y: usize) -> usize {
+        self.rows.get(&y).copied().unwrap_or(self.default)
+    }
+
+    pub fn column(&self, x: usize) -> usize {
+        self.columns.get(&x).copied().unwrap_or(self.default)
+    }
+}
+
+/// Sum of the distances between every pair of galaxies, one axis at a time:
+/// with the coordinates sorted, the `i`th one is subtracted from the `i` after it.
+pub fn distance_sum(image: &str, expansion: &Expansion) -> usize {
+    let galaxies = expand(image, expansion);
+    let (mut ys, mut xs): (Vec<_>, Vec<_>) = galaxies.iter().map(|p| (p.y, p.x)).unzip();
+    ys.sort_unstable();
+    xs.sort_unstable();
+
+    [ys, xs]
+        .iter()
+        .map(|coords| {
+            let mut prefix = 0;
+            coords
+                .iter()
+                .enumerate()
+                .map(|(i, &coord)| {
+                    let sum = coord * i - prefix;
+                    prefix += coord;
+                    sum
+                })
+                .sum::<usize>()
+        })
+        .sum()
+}
+
+/// Galaxy positions after expansion.
+pub fn expand(image: &str, expansion: &Expansion) -> Vec<Point> {
+    let mut populated_y = Vec::new();
+    let mut populated_x = Vec::new();
+
+    let mut galaxies = Vec::new();
+    for (y, line) in image.lines().enumerate() {
+        for (x, c) in line.char_indices() {
+            match c {
+                '#' => {
+                    if y + 1 > populated_y.len() {
+                        populated_y.resize(y + 1, false);
+                    }
+                    populated_y[y] = true;
+
+                    if x + 1 > populated_x.len() {
+                        populated_x.resize(x + 1, false);
+                    }
+                    populated_x[x] = true;
+
+                    galaxies.push(Point {
+                        y: y as _,
+                        x: x as _,
+                    });
+                }
+                '.' => (),
+                c => panic!("{c}"),
+            }
+        }
+    }
+
+    // position of every row and column after the ones before it have expanded
+    let mut y_position = 0;
+    let y_positions = populated_y
+        .into_iter()
+        .enumerate()
+        .map(|(y, is_populated)| {
+            let position = y_position;
+            y_position += if is_populated { 1 } else { expansion.row(y) };
+            position
+        })
+        .collect::<Vec<_>>();
+
+    let mut x_position = 0;
+    let x_positions = populated_x
+        .into_iter()
+        .enumerate()
+        .map(|(x, is_populated)| {
+            let position = x_position;
+            x_position += if is_populated { 1 } else { expansion.column(x) };
+            position
+        })
+        .collect::<Vec<_>>();
+
+    galaxies
+        .into_iter()
+        .map(|Point { y, x }| Point {
+            y: y_positions[y],
+            x: x_positions[x],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::{distance_sum, Expansion, Point};
+
+    const IMAGE: &str = concat! {
+        "...#......\n",
+        ".......#..\n",
+        "#.........\n",
+        "..........\n",
+        "......#...\n",
+        ".#........\n",
+        ".........#\n",
+        "..........\n",
+        ".......#..\n",
+        "#...#.....\n",
+    };
+
+    /// The original solver, every empty line `expansion` rows or columns wider.
+    fn shortest_paths(image: &str, expansion: usize) -> usize {
+        let mut populated_y = Vec::new();
+        let mut populated_x = Vec::new();
+
+        let mut galaxies = Vec::new();
+        for (y, line) in image.lines().enumerate() {
+            for (x, c) in line.char_indices() {
+                match c {
+                    '#' => {
+                        if y + 1 > populated_y.len() {
+                            populated_y.resize(y + 1, false);
+                        }
+                        populated_y[y] = true;
+
+                        if x + 1 > populated_x.len() {
+                            populated_x.resize(x + 1, false);
+                        }
+                        populated_x[x] = true;
+
+                        galaxies.push(Point { y, x });
+                    }
+                    '.' => (),
+                    c => panic!("{c}"),
+                }
+            }
+        }
+
+        let mut y_offset = 0;
+        let y_offsets = populated_y
+            .into_iter()
+            .map(|is_populated| {
+                if !is_populated {
+                    y_offset += 1;
+                }
+                y_offset
+            })
+            .collect::<Vec<_>>();
+
+        let mut x_offset = 0;
+        let x_offsets = populated_x
+            .into_iter()
+            .map(|is_populated| {
+                if !is_populated {
+                    x_offset += 1;
+                }
+                x_offset
+            })
+            .collect::<Vec<_>>();
+
+        let galaxies = galaxies
+            .into_iter()
+            .map(|Point { y, x }| Point {
+                y: y + (y_offsets.get(y).copied().unwrap_or_default() * expansion),
+                x: x + (x_offsets.get(x).copied().unwrap_or_default() * expansion),
+            })
+            .collect::<Vec<_>>();
+
+        galaxies
+            .iter()
+            .enumerate()
+            .flat_map(|(i, galaxy_0)| {
+                galaxies[i + 1..].iter().map(|galaxy_1| {
+                    galaxy_0.y.abs_diff(galaxy_1.y) + galaxy_0.x.abs_diff(galaxy_1.x)
+                })
+            })
+            .sum()
+    }
+
+    /// Every pair compared, each galaxy placed by adding up the widths of the lines before it.
+    fn pairwise(image: &str, expansion: &Expansion) -> usize {
+        let galaxies = image
+            .lines()
+            .enumerate()
+            .flat_map(|(y, line)| {
+                line.char_indices()
+                    .filter(|&(_, c)| c == '#')
+                    .map(move |(x, _)| Point { y, x })
+            })
+            .collect::<Vec<_>>();
+        let row_width = |y: usize| {
+            let populated = galaxies.iter().any(|galaxy| galaxy.y == y);
+            if populated {
+                1
+            } else {
+                expansion.row(y)
+            }
+        };
+        let column_width = |x: usize| {
+            let populated = galaxies.iter().any(|galaxy| galaxy.x == x);
+            if populated {
+                1
+            } else {
+                expansion.column(x)
+            }
+        };
+        let galaxies = galaxies
+            .iter()
+            .map(|galaxy| Point {
+                y: (0..galaxy.y).map(row_width).sum(),
+                x: (0..galaxy.x).map(column_width).sum(),
+            })
+            .collect::<Vec<_>>();
+
+        let mut sum = 0;
+        for (i, galaxy_0) in galaxies.iter().enumerate() {
+            for galaxy_1 in &galaxies[i + 1..] {
+                sum += galaxy_0.y.abs_diff(galaxy_1.y) + galaxy_0.x.abs_diff(galaxy_1.x);
+            }
+        }
+        sum
+    }
+
+    #[test]
+    fn per_line_expansion() {
+        // empty rows 3 and 7, empty columns 2, 5 and 8
+        let expansion = Expansion::parse("default 1\nrow 3 2\n\ncolumn 5 10\ncolumn 0 7\n");
+        assert_eq!(expansion.row(3), 2);
+        assert_eq!(expansion.row(7), 1);
+        assert_eq!(expansion.column(5), 10);
+        assert_eq!(distance_sum(IMAGE, &expansion), pairwise(IMAGE, &expansion));
+        assert_eq!(distance_sum(IMAGE, &Expansion::parse("")), 374);
+        assert_eq!(shortest_paths(IMAGE, 1), 374);
+        assert_eq!(shortest_paths(IMAGE, 99), 8410);
+    }
+
+    #[test]
+    fn matches_pairwise() {
+        let mut rng = StdRng::seed_from_u64(11);
+        for _ in 0..200 {
+            let (height, width) = (rng.gen_range(1..15), rng.gen_range(1..15));
+            let image = (0..height)
+                .map(|_| {
+                    let mut line = (0..width)
+                        .map(|_| if rng.gen_bool(0.15) { '#' } else { '.' })
+                        .collect::<String>();
+                    line.push('\n');
+                    line
+                })
+                .collect::<String>();
+
+            let extra = rng.gen_range(0..1000);
+            assert_eq!(
+                distance_sum(&image, &Expansion::uniform(extra + 1)),
+                shortest_paths(&image, extra),
+                "{image}"
+            );
+
+            let mut expansion = Expansion::uniform(rng.gen_range(1..1000));
+            for y in 0..height {
+                if rng.gen_bool(0.5) {
+                    expansion.rows.insert(y, rng.gen_range(0..1000));
+                }
+            }
+            for x in 0..width {
+                if rng.gen_bool(0.5) {
+                    expansion.columns.insert(x, rng.gen_range(0..1000));
+                }
+            }
+            assert_eq!(
+                distance_sum(&image, &expansion),
+                pairwise(&image, &expansion),
+                "{image}"
+            );
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use crate::{expand, kd_tree::KdTree, Expansion, Point};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Metric {
+    Manhattan,
+    Chebyshev,
+    /// squared, so distances stay exact
+    Euclidean,
+}
+
+impl Metric {
+    fn combine(self, dy: usize, dx: usize) -> u128 {
+        let (dy, dx) = (dy as u128, dx as u128);
+        match self {
+            Metric::Manhattan => dy + dx,
+            Metric::Chebyshev => dy.max(dx),
+            Metric::Euclidean => dy * dy + dx * dx,
+        }
+    }
+
+    pub fn distance(self, a: Point, b: Point) -> u128 {
+        self.combine(a.y.abs_diff(b.y), a.x.abs_diff(b.x))
+    }
+
+    /// Closest and farthest any point in the box with corners `(min, max)` can be from `point`.
+    pub(crate) fn range(self, point: Point, (min, max): (Point, Point)) -> (u128, u128) {
+        let gap = |p: usize, lo: usize, hi: usize| lo.saturating_sub(p).max(p.saturating_sub(hi));
+        let reach = |p: usize, lo: usize, hi: usize| p.abs_diff(lo).max(p.abs_diff(hi));
+        (
+            self.combine(gap(point.y, min.y, max.y), gap(point.x, min.x, max.x)),
+            self.combine(reach(point.y, min.y, max.y), reach(point.x, min.x, max.x)),
+        )
+    }
+}
+
+/// Expanded galaxy positions, indexed in reading order of the image.
+#[derive(Debug, Clone)]
+pub struct Universe {
+    galaxies: Vec<Point>,
+    index: KdTree,
+}
+
+impl Universe {
+    pub fn new(image: &str, expansion: &Expansion) -> Self {
+        Self::from_galaxies(expand(image, expansion))
+    }
+
+    /// For generated universes, positions already expanded.
+    pub fn from_galaxies(galaxies: Vec<Point>) -> Self {
+        Self {
+            index: KdTree::new(galaxies.clone()),
+            galaxies,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.galaxies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.galaxies.is_empty()
+    }
+
+    pub fn galaxy(&self, i: usize) -> Point {
+        self.galaxies[i]
+    }
+
+    pub fn distance(&self, i: usize, j: usize, metric: Metric) -> u128 {
+        metric.distance(self.galaxies[i], self.galaxies[j])
+    }
+
+    /// Closest other galaxy to galaxy `i` and its distance, the lower index on a tie.
+    pub fn nearest(&self, i: usize, metric: Metric) -> Option<(usize, u128)> {
+        self.index
+            .nearest(i, 1, metric)
+            .first()
+            .map(|&(distance, j)| (j, distance))
+    }
+
+    /// The `k` closest pairs `(i, j, distance)` with `i < j`, closest first.
+    pub fn closest_pairs(&self, k: usize, metric: Metric) -> Vec<(usize, usize, u128)> {
+        // every pair among the k closest is among the k nearest neighbours of its lower index
+        let mut pairs = (0..self.len())
+            .flat_map(|i| {
+                self.index
+                    .nearest(i, k, metric)
+                    .into_iter()
+                    .filter(move |&(_, j)| j > i)
+                    .map(move |(distance, j)| (distance, i, j))
+            })
+            .collect::<Vec<_>>();
+        pairs.sort_unstable();
+        pairs
+            .into_iter()
+            .take(k)
+            .map(|(distance, i, j)| (i, j, distance))
+            .collect()
+    }
+
+    /// The `k` farthest pairs `(i, j, distance)` with `i < j`, farthest first.
+    pub fn farthest_pairs(&self, k: usize, metric: Metric) -> Vec<(usize, usize, u128)> {
+        let mut pairs = (0..self.len())
+            .flat_map(|i| {
+                self.index
+                    .farthest(i, k, metric)
+                    .into_iter()
+                    .filter(move |&(_, j)| j > i)
+                    .map(move |(distance, j)| (u128::MAX - distance, i, j))
+            })
+            .collect::<Vec<_>>();
+        pairs.sort_unstable();
+        pairs
+            .into_iter()
+            .take(k)
+            .map(|(distance, i, j)| (i, j, u128::MAX - distance))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::{Metric, Universe};
+    use crate::{Expansion, Point};
+
+    const IMAGE: &str = concat! {
+        "...#......\n",
+        ".......#..\n",
+        "#.........\n",
+        "..........\n",
+        "......#...\n",
+        ".#........\n",
+        ".........#\n",
+        "..........\n",
+        ".......#..\n",
+        "#...#.....\n",
+    };
+
+    const METRICS: [Metric; 3] = [Metric::Manhattan, Metric::Chebyshev, Metric::Euclidean];
+
+    fn all_pairs(universe: &Universe, metric: Metric) -> Vec<(usize, usize, u128)> {
+        let mut pairs = Vec::new();
+        for i in 0..universe.len() {
+            for j in i + 1..universe.len() {
+                pairs.push((i, j, universe.distance(i, j, metric)));
+            }
+        }
+        pairs.sort_unstable_by_key(|&(i, j, distance)| (distance, i, j));
+        pairs
+    }
+
+    #[test]
+    fn sample() {
+        let universe = Universe::new(IMAGE, &Expansion::uniform(2));
+        assert_eq!(universe.len(), 9);
+        assert_eq!(universe.distance(4, 8, Metric::Manhattan), 9);
+        assert_eq!(universe.distance(0, 6, Metric::Manhattan), 15);
+        assert_eq!(universe.distance(2, 5, Metric::Manhattan), 17);
+        assert_eq!(universe.distance(7, 8, Metric::Manhattan), 5);
+        assert_eq!(universe.distance(2, 5, Metric::Chebyshev), 12);
+        assert_eq!(universe.distance(2, 5, Metric::Euclidean), 5 * 5 + 12 * 12);
+
+        let farthest = universe.farthest_pairs(1, Metric::Manhattan);
+        assert_eq!(farthest.len(), 1);
+        assert_eq!(
+            farthest[0].2,
+            all_pairs(&universe, Metric::Manhattan).last().unwrap().2
+        );
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(43);
+        for _ in 0..30 {
+            let galaxies = (0..rng.gen_range(2..80))
+                .map(|_| Point {
+                    y: rng.gen_range(0..30),
+                    x: rng.gen_range(0..30),
+                })
+                .collect::<Vec<_>>();
+            let universe = Universe::from_galaxies(galaxies);
+            for metric in METRICS {
+                let pairs = all_pairs(&universe, metric);
+                for k in [0, 1, 5, 40, pairs.len() + 3] {
+                    let closest = pairs.iter().copied().take(k).collect::<Vec<_>>();
+                    assert_eq!(universe.closest_pairs(k, metric), closest);
+
+                    let mut farthest = pairs.clone();
+                    farthest.sort_unstable_by_key(|&(i, j, distance)| {
+                        (std::cmp::Reverse(distance), i, j)
+                    });
+                    farthest.truncate(k);
+                    assert_eq!(universe.farthest_pairs(k, metric), farthest);
+                }
+
+                for i in 0..universe.len() {
+                    let nearest = (0..universe.len())
+                        .filter(|&j| j != i)
+                        .map(|j| (j, universe.distance(i, j, metric)))
+                        .min_by_key(|&(j, distance)| (distance, j));
+                    assert_eq!(universe.nearest(i, metric), nearest);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn large_universe() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let galaxies = (0..20_000)
+            .map(|_| Point {
+                y: rng.gen_range(0..1_000_000_000),
+                x: rng.gen_range(0..1_000_000_000),
+            })
+            .collect::<Vec<_>>();
+        let universe = Universe::from_galaxies(galaxies);
+        let closest = universe.closest_pairs(10, Metric::Euclidean);
+        assert_eq!(closest.len(), 10);
+        assert!(closest.windows(2).all(|pair| pair[0].2 <= pair[1].2));
+        let (i, j, distance) = closest[0];
+        assert_eq!(universe.nearest(i, Metric::Euclidean), Some((j, distance)));
+    }
+}
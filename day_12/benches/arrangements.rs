@@ -1,11 +1,20 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use day_12::{parse_row, try_match};
+use day_12::{count_arrangements, parse_row, try_match, unfold};
 
 pub fn criterion_benchmark(c: &mut Criterion) {
     let (record, groups) = parse_row("?????#???????????#?# 1,4,1,2,1,1");
-    let (record, groups) = (record.into_iter(), groups.into_iter());
+    let (record_iter, groups_iter) = (record.clone().into_iter(), groups.clone().into_iter());
     c.bench_function("arrangements", |b| {
-        b.iter(|| try_match(black_box(record.clone()), black_box(groups.clone()), &[]))
+        b.iter(|| {
+            try_match(
+                black_box(record_iter.clone()),
+                black_box(groups_iter.clone()),
+            )
+        })
+    });
+    let (record, groups) = unfold(&record, &groups, 5);
+    c.bench_function("arrangements x5", |b| {
+        b.iter(|| count_arrangements(black_box(&record), black_box(&groups)))
     });
 }
 
@@ -0,0 +1,223 @@
+use rand::Rng;
+
+use crate::{Counter, State};
+
+/// Every concrete arrangement consistent with a record and its groups, operational
+/// springs before damaged ones at each unknown. Branches without arrangements are
+/// skipped using the DP counts, so each one costs at most a pass over the record.
+pub struct Arrangements<'r> {
+    counter: Counter<'r>,
+    /// chosen states so far
+    current: Vec<State>,
+    /// group index and run length before each chosen state
+    before: Vec<(usize, usize)>,
+    started: bool,
+}
+
+impl<'r> Arrangements<'r> {
+    pub fn new(record: &'r [State], groups: &'r [usize]) -> Self {
+        Self {
+            counter: Counter::new(record, groups),
+            current: Vec::with_capacity(record.len()),
+            before: Vec::with_capacity(record.len()),
+            started: false,
+        }
+    }
+
+    /// Follows `state` from the end of `current` if any arrangement continues from there.
+    fn push(&mut self, state: State) -> bool {
+        let pos = self.current.len();
+        let (group, run) = self.before.last().map_or((0, 0), |&(group, run)| {
+            let last = self.current[pos - 1];
+            self.counter.step(pos - 1, group, run, last).unwrap()
+        });
+        match self.counter.step(pos, group, run, state) {
+            Some((next_group, next_run))
+                if self.counter.count(pos + 1, next_group, next_run) != Some(0) =>
+            {
+                self.current.push(state);
+                self.before.push((group, run));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Completes `current` with the first arrangement below it.
+    fn descend(&mut self) {
+        while self.current.len() < self.counter.len() {
+            if !self.push(State::Operational) {
+                assert!(self.push(State::Damaged));
+            }
+        }
+    }
+}
+
+impl Iterator for Arrangements<'_> {
+    type Item = Vec<State>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.started {
+            self.started = true;
+            if self.counter.count(0, 0, 0) == Some(0) {
+                return None;
+            }
+        } else {
+            // back up to the last operational choice that could have been damaged
+            loop {
+                let state = self.current.pop()?;
+                self.before.pop();
+                if state == State::Operational && self.push(State::Damaged) {
+                    break;
+                }
+            }
+        }
+        self.descend();
+        Some(self.current.clone())
+    }
+}
+
+/// Draws arrangements uniformly at random, weighting each choice by the number
+/// of arrangements that follow it.
+pub struct Sampler<'r> {
+    counter: Counter<'r>,
+}
+
+impl<'r> Sampler<'r> {
+    pub fn new(record: &'r [State], groups: &'r [usize]) -> Self {
+        Self {
+            counter: Counter::new(record, groups),
+        }
+    }
+
+    /// `None` if there are too many to fit in a `u128`.
+    pub fn count(&self) -> Option<u128> {
+        self.counter.count(0, 0, 0)
+    }
+
+    /// `None` if there are no arrangements, or too many to count.
+    pub fn sample(&self, rng: &mut impl Rng) -> Option<Vec<State>> {
+        if self.count()? == 0 {
+            return None;
+        }
+
+        let (mut group, mut run) = (0, 0);
+        let mut arrangement = Vec::with_capacity(self.counter.len());
+        for pos in 0..self.counter.len() {
+            let [operational, damaged] = [State::Operational, State::Damaged].map(|state| {
+                let next = self.counter.step(pos, group, run, state)?;
+                // no more than the total, so never overflows
+                Some((next, self.counter.count(pos + 1, next.0, next.1)?))
+            });
+            let operational_count = operational.map_or(0, |(_, count)| count);
+            let damaged_count = damaged.map_or(0, |(_, count)| count);
+
+            let (state, next) =
+                if rng.gen_range(0..operational_count + damaged_count) < operational_count {
+                    (State::Operational, operational.unwrap().0)
+                } else {
+                    (State::Damaged, damaged.unwrap().0)
+                };
+            arrangement.push(state);
+            (group, run) = next;
+        }
+        Some(arrangement)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::{Arrangements, Sampler};
+    use crate::{count_arrangements, parse_row, unfold, State};
+
+    const RECORDS: &[&str] = &[
+        "???.### 1,1,3",
+        ".??..??...?##. 1,1,3",
+        "?#?#?#?#?#?#?#? 1,3,1,6",
+        "????.#...#... 4,1,1",
+        "????.######..#####. 1,6,5",
+        "?###???????? 3,2,1",
+        "#.# 2",
+    ];
+
+    fn groups_of(arrangement: &[State]) -> Vec<usize> {
+        arrangement
+            .split(|&state| state == State::Operational)
+            .map(<[State]>::len)
+            .filter(|&len| len > 0)
+            .collect()
+    }
+
+    #[test]
+    fn arrangements() {
+        for line in RECORDS {
+            let (record, groups) = parse_row(line);
+            let (record, groups) = unfold(&record, &groups, 2);
+            let arrangements = Arrangements::new(&record, &groups).collect::<Vec<_>>();
+
+            assert_eq!(
+                Some(arrangements.len() as u128),
+                count_arrangements(&record, &groups)
+            );
+            // operational sorts before damaged, so they come out in order without repeats
+            assert!(arrangements.windows(2).all(|pair| {
+                let key = |arrangement: &[State]| {
+                    arrangement
+                        .iter()
+                        .map(|&state| state == State::Damaged)
+                        .collect::<Vec<_>>()
+                };
+                key(&pair[0]) < key(&pair[1])
+            }));
+            for arrangement in &arrangements {
+                assert_eq!(groups_of(arrangement), groups, "{line}");
+                assert!(record
+                    .iter()
+                    .zip(arrangement)
+                    .all(|(&known, &state)| known == State::Unknown || known == state));
+            }
+        }
+    }
+
+    #[test]
+    fn lazy() {
+        let (record, groups) = parse_row("?????#???????????#?# 1,4,1,2,1,1");
+        let (record, groups) = unfold(&record, &groups, 5);
+        let first = Arrangements::new(&record, &groups)
+            .take(3)
+            .collect::<Vec<_>>();
+        assert_eq!(first.len(), 3);
+        // the joining unknown makes the trailing run a group of 4
+        assert_eq!(
+            first[0].iter().map(State::to_string).collect::<String>()[..21],
+            *".....#...........####"
+        );
+        assert!(first
+            .iter()
+            .all(|arrangement| groups_of(arrangement) == groups));
+    }
+
+    #[test]
+    fn sample_uniformly() {
+        let (record, groups) = parse_row("?###???????? 3,2,1");
+        let sampler = Sampler::new(&record, &groups);
+        assert_eq!(sampler.count(), Some(10));
+
+        let mut rng = StdRng::seed_from_u64(12);
+        let mut seen = HashMap::new();
+        for _ in 0..10_000 {
+            let arrangement = sampler.sample(&mut rng).unwrap();
+            assert_eq!(groups_of(&arrangement), groups);
+            *seen.entry(arrangement).or_insert(0) += 1;
+        }
+        assert_eq!(seen.len(), 10);
+        assert!(seen.values().all(|&n| (850..1150).contains(&n)), "{seen:?}");
+
+        let (record, groups) = parse_row("#.# 2");
+        assert_eq!(Sampler::new(&record, &groups).sample(&mut rng), None);
+    }
+}
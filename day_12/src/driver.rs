@@ -0,0 +1,120 @@
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+use owo_colors::OwoColorize;
+
+use crate::{count_arrangements, parse_row, unfold};
+
+/// What [`Driver::run`] writes to stderr while it works.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Verbosity {
+    #[default]
+    Quiet,
+    /// records done so far, rewritten in place
+    Progress,
+    /// every record and its count
+    Verbose,
+}
+
+/// Counts arrangements for many records at once, split across worker threads.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Driver {
+    pub workers: usize,
+    /// copies of every record and its groups, as for [`unfold`]
+    pub unfold: usize,
+    pub verbosity: Verbosity,
+}
+
+impl Default for Driver {
+    fn default() -> Self {
+        Self {
+            workers: thread::available_parallelism().map_or(1, usize::from),
+            unfold: 1,
+            verbosity: Verbosity::Quiet,
+        }
+    }
+}
+
+impl Driver {
+    /// Arrangement count of every line, in input order, `None` where it overflows.
+    pub fn run(&self, lines: &[&str]) -> Vec<Option<u128>> {
+        if lines.is_empty() {
+            return Vec::new();
+        }
+        let chunk_len = lines.len().div_ceil(self.workers.max(1));
+        let done = AtomicUsize::new(0);
+
+        let counts = thread::scope(|scope| {
+            let workers = lines
+                .chunks(chunk_len)
+                .map(|chunk| {
+                    let done = &done;
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|line| {
+                                let count = self.count(line);
+                                let done = done.fetch_add(1, Ordering::Relaxed) + 1;
+                                self.report(line, count, done, lines.len());
+                                count
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap())
+                .collect()
+        });
+
+        if self.verbosity == Verbosity::Progress {
+            eprintln!();
+        }
+        counts
+    }
+
+    fn count(&self, line: &str) -> Option<u128> {
+        let (record, groups) = parse_row(line);
+        let (record, groups) = unfold(&record, &groups, self.unfold);
+        count_arrangements(&record, &groups)
+    }
+
+    fn report(&self, line: &str, count: Option<u128>, done: usize, total: usize) {
+        match (self.verbosity, count) {
+            (Verbosity::Quiet, _) => (),
+            (Verbosity::Progress, _) => eprint!("\r{done}/{total}"),
+            (Verbosity::Verbose, Some(count)) => eprintln!("{} {}", line.blue(), count.red()),
+            (Verbosity::Verbose, None) => eprintln!("{} {}", line.blue(), "overflow".red()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Driver;
+    use crate::{count_arrangements, parse_row};
+
+    #[test]
+    fn keeps_input_order() {
+        let lines = include_str!("input.txt").lines().collect::<Vec<_>>();
+        let expected = lines
+            .iter()
+            .map(|line| {
+                let (record, groups) = parse_row(line);
+                count_arrangements(&record, &groups)
+            })
+            .collect::<Vec<_>>();
+
+        for workers in [0, 1, 3, 8, lines.len() + 5] {
+            let driver = Driver {
+                workers,
+                ..Driver::default()
+            };
+            assert_eq!(driver.run(&lines), expected, "{workers} workers");
+        }
+        assert!(Driver::default().run(&[]).is_empty());
+    }
+}
@@ -1,6 +1,14 @@
 use std::fmt::Display;
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+mod arrangements;
+mod driver;
+mod nonogram;
+
+pub use arrangements::{Arrangements, Sampler};
+pub use driver::{Driver, Verbosity};
+pub use nonogram::{solve_line, ClueError, Grid, Nonogram, Solution};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum State {
     Operational,
     Damaged,
@@ -36,111 +44,251 @@ pub fn parse_row(line: &str) -> (Vec<State>, Vec<usize>) {
     (record, groups)
 }
 
+/// `n` copies of the record joined by unknown springs, and `n` copies of the groups.
+pub fn unfold(record: &[State], groups: &[usize], n: usize) -> (Vec<State>, Vec<usize>) {
+    let mut unfolded = Vec::with_capacity((record.len() + 1) * n);
+    for i in 0..n {
+        if i > 0 {
+            unfolded.push(State::Unknown);
+        }
+        unfolded.extend_from_slice(record);
+    }
+    (unfolded, groups.repeat(n))
+}
+
+/// Number of ways to fill in the unknown springs so the damaged runs match `groups`,
+/// `None` if it does not fit in a `u128`.
+pub fn count_arrangements(record: &[State], groups: &[usize]) -> Option<u128> {
+    let runs = Runs::new(record, groups);
+    // a group and the spring after it are the furthest a row looks ahead
+    let window = groups.iter().copied().max().unwrap_or_default() + 2;
+    let mut rows = vec![Vec::new(); window];
+    for pos in (0..=record.len()).rev() {
+        let row = runs.row(pos, |pos, group| rows[pos % window][group]);
+        rows[pos % window] = row;
+    }
+    rows[0][0]
+}
+
+/// A record and its groups, placing one whole group at a time.
+struct Runs<'r> {
+    record: &'r [State],
+    groups: &'r [usize],
+    /// operational springs before each position
+    operational: Vec<usize>,
+}
+
+impl<'r> Runs<'r> {
+    fn new(record: &'r [State], groups: &'r [usize]) -> Self {
+        let mut operational = Vec::with_capacity(record.len() + 1);
+        operational.push(0);
+        for (pos, &state) in record.iter().enumerate() {
+            operational.push(operational[pos] + (state == State::Operational) as usize);
+        }
+        Self {
+            record,
+            groups,
+            operational,
+        }
+    }
+
+    /// Whether `len` damaged springs from `pos` can be followed by an operational one or the end.
+    fn ends_run(&self, pos: usize, len: usize) -> bool {
+        let end = pos + len;
+        end <= self.record.len()
+            && self.operational[end] == self.operational[pos]
+            && self.record.get(end) != Some(&State::Damaged)
+    }
+
+    /// Position after a run of `len` from `pos` and the spring that ends it.
+    fn after(&self, pos: usize, len: usize) -> usize {
+        (pos + len + 1).min(self.record.len())
+    }
+
+    /// Arrangements of `record[pos..]` for each number of groups already placed, the spring
+    /// before `pos` not being damaged. `later` gives the same for positions after `pos`.
+    fn row(&self, pos: usize, later: impl Fn(usize, usize) -> Option<u128>) -> Vec<Option<u128>> {
+        (0..=self.groups.len())
+            .map(|group| {
+                if pos == self.record.len() {
+                    return Some((group == self.groups.len()) as u128);
+                }
+                let skip = match self.record[pos] {
+                    State::Damaged => 0,
+                    _ => later(pos + 1, group)?,
+                };
+                let place = match self.groups.get(group) {
+                    Some(&len) if len > 0 && self.ends_run(pos, len) => {
+                        later(self.after(pos, len), group + 1)?
+                    }
+                    _ => 0,
+                };
+                skip.checked_add(place)
+            })
+            .collect()
+    }
+}
+
+/// Arrangement counts for every (record position, group index, length of the damaged run
+/// being read), from a table over positions and groups filled in from the end.
+pub(crate) struct Counter<'r> {
+    runs: Runs<'r>,
+    /// [`Runs::row`] of every position, `None` where a count overflows
+    rows: Vec<Vec<Option<u128>>>,
+}
+
+impl<'r> Counter<'r> {
+    pub(crate) fn new(record: &'r [State], groups: &'r [usize]) -> Self {
+        let runs = Runs::new(record, groups);
+        let mut rows = vec![Vec::new(); record.len() + 1];
+        for pos in (0..=record.len()).rev() {
+            let row = runs.row(pos, |pos, group| rows[pos][group]);
+            rows[pos] = row;
+        }
+        Self { runs, rows }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.runs.record.len()
+    }
+
+    /// Group index and run length after reading `state` at `pos`, if the record and groups allow it.
+    pub(crate) fn step(
+        &self,
+        pos: usize,
+        group: usize,
+        run: usize,
+        state: State,
+    ) -> Option<(usize, usize)> {
+        let groups = self.runs.groups;
+        match (self.runs.record[pos], state) {
+            (State::Damaged, State::Operational) | (State::Operational, State::Damaged) => None,
+            (_, State::Operational) if run == 0 => Some((group, 0)),
+            (_, State::Operational) if groups[group] == run => Some((group + 1, 0)),
+            (_, State::Damaged) if group < groups.len() && run < groups[group] => {
+                Some((group, run + 1))
+            }
+            _ => None,
+        }
+    }
+
+    /// Arrangements of `record[pos..]` given the groups and run so far, `None` on overflow.
+    pub(crate) fn count(&self, pos: usize, group: usize, run: usize) -> Option<u128> {
+        if run == 0 {
+            return self.rows[pos][group];
+        }
+        let rest = self.runs.groups[group] - run;
+        if !self.runs.ends_run(pos, rest) {
+            return Some(0);
+        }
+        self.rows[self.runs.after(pos, rest)][group + 1]
+    }
+}
+
+/// Exponential matcher kept as a reference for [`count_arrangements`].
 #[inline]
 pub fn try_match(
     mut record: impl Iterator<Item = State> + Clone,
     mut groups: impl Iterator<Item = usize> + Clone,
-    dis_buf: &[State],
 ) -> usize {
     let Some(group) = groups.next() else {
-        let mut record_len = 0;
-        if record.all(|s| {
-            record_len += 1;
-            s != State::Damaged
-        }) {
-            // let dis_buf = dis_buf
-            //     .iter()
-            //     .copied()
-            //     .chain(iter::repeat(State::Operational).take(record_len));
-            // fmt(dis_buf);
-            // compare(og.iter().copied(), dis_buf);
-            // eprintln!(" ✅ end of groups");
-            return 1;
-        } else {
-            return 0;
-        }
+        return record.all(|s| s != State::Damaged) as usize;
     };
 
     let mut sum = 0;
-    let mut i = 0;
     'record_loop: loop {
-        // let mut dis_buf = dis_buf
-        //     .iter()
-        //     .copied()
-        //     .chain(iter::repeat(State::Operational).take(i))
-        //     .collect::<Vec<_>>();
-
         'try_arr: {
             let mut record = record.clone();
             for _ in 0..group {
                 match record.next() {
-                    Some(State::Operational) => {
-                        // fmt(dis_buf.iter().copied());
-                        // eprintln!(" X invalid group");
-                        break 'try_arr;
-                    }
+                    Some(State::Operational) => break 'try_arr,
                     Some(State::Damaged | State::Unknown) => (),
-                    None => {
-                        // fmt(dis_buf.iter().copied());
-                        // eprintln!(" X group end of record");
-                        break 'record_loop;
-                    }
+                    None => break 'record_loop,
                 }
             }
 
-            // dis_buf.extend(iter::repeat(State::Damaged).take(group));
-
             match record.next() {
                 None => {
                     if groups.next().is_some() {
-                        // fmt(dis_buf);
-                        // eprintln!(" X sep end of record, remaining groups");
                         break 'record_loop;
                     } else {
-                        // fmt(dis_buf.iter().copied());
-                        // compare(record.iter().copied(), dis_buf);
-                        // eprintln!(" ✅ sep end of record, no groups");
                         sum += 1;
                     }
                 }
-
-                Some(State::Damaged) => {
-                    // fmt(dis_buf.iter().copied());
-                    // eprintln!(" X missing sep");
-                }
+                Some(State::Damaged) => (),
                 Some(State::Operational | State::Unknown) => {
-                    // dis_buf.push(State::Operational);
-
-                    let arrangements = try_match(record, groups.clone(), &dis_buf);
-                    sum += arrangements
+                    sum += try_match(record, groups.clone());
                 }
             }
         }
 
         match record.next() {
             Some(State::Damaged) | None => break,
-            Some(State::Operational | State::Unknown) => {
-                // dis_buf.push(State::Operational);
-                i += 1;
-            }
+            Some(State::Operational | State::Unknown) => (),
         }
     }
     sum
 }
 
-fn fmt(record: impl IntoIterator<Item = State>) {
-    for s in record {
-        eprint!("{s}");
+#[cfg(test)]
+mod tests {
+    use super::{count_arrangements, parse_row, try_match, unfold, State};
+
+    #[test]
+    fn unfold_any_factor() {
+        let (record, groups) = parse_row(".# 1");
+        assert_eq!(unfold(&record, &groups, 0), (vec![], vec![]));
+        assert_eq!(
+            unfold(&record, &groups, 1),
+            (record.clone(), groups.clone())
+        );
+        let (unfolded, unfolded_groups) = unfold(&record, &groups, 3);
+        assert_eq!(
+            unfolded.iter().map(State::to_string).collect::<String>(),
+            ".#?.#?.#"
+        );
+        assert_eq!(unfolded_groups, [1, 1, 1]);
     }
-}
 
-fn compare(expected: impl IntoIterator<Item = State>, got: impl IntoIterator<Item = State>) {
-    for (i, pair) in expected.into_iter().zip(got).enumerate() {
-        match pair {
-            (State::Unknown, _) => (),
-            (_, State::Unknown) => (),
-            (expected, got) => assert_eq!(expected, got, "#{i}: {expected} <--> {got}"),
+    #[test]
+    fn matches_try_match() {
+        for line in include_str!("input.txt").lines().take(100) {
+            let (record, groups) = parse_row(line);
+            for n in 1..=2 {
+                let (record, groups) = unfold(&record, &groups, n);
+                let expected = try_match(record.iter().copied(), groups.iter().copied());
+                assert_eq!(
+                    count_arrangements(&record, &groups),
+                    Some(expected as u128),
+                    "{line} x{n}"
+                );
+            }
         }
     }
-}
 
+    #[test]
+    fn large_unfold() {
+        // the groups and the gaps between them fill the unfolded record exactly
+        let (record, groups) = parse_row("??? 3");
+        let (record, groups) = unfold(&record, &groups, 100);
+        assert_eq!(count_arrangements(&record, &groups), Some(1));
+
+        let (record, groups) = parse_row("???.### 1,1,3");
+        let (unfolded, unfolded_groups) = unfold(&record, &groups, 20);
+        assert_eq!(count_arrangements(&unfolded, &unfolded_groups), Some(1));
+        let (unfolded, unfolded_groups) = unfold(&record, &groups, 200);
+        assert_eq!(count_arrangements(&unfolded, &unfolded_groups), Some(1));
+    }
+
+    #[test]
+    fn overflow() {
+        let (record, groups) = parse_row("?###???????? 3,2,1");
+        let (unfolded, unfolded_groups) = unfold(&record, &groups, 20);
+        assert_eq!(
+            count_arrangements(&unfolded, &unfolded_groups),
+            Some(10 * 15u128.pow(19))
+        );
+        let (record, groups) = unfold(&record, &groups, 200);
+        assert_eq!(count_arrangements(&record, &groups), None);
+    }
+}
@@ -1,51 +1,44 @@
-use std::iter::once;
+use std::io::IsTerminal;
 
-use day_12::{parse_row, try_match, State};
-use owo_colors::OwoColorize;
+use day_12::{Driver, Verbosity};
 
 const INPUT: &str = include_str!("input.txt");
 
 fn main() {
-    let arrangement_count = INPUT.lines().map(arrangements).sum::<usize>();
-    println!("Arrangement count: {arrangement_count}");
-    // 7857
-
-    let arrangement_count = INPUT.lines().map(arrangements_x5).sum::<usize>();
-    println!("Arrangement count x5: {arrangement_count}");
-    //
-}
+    let lines = INPUT.lines().collect::<Vec<_>>();
+    let driver = Driver {
+        verbosity: if std::io::stderr().is_terminal() {
+            Verbosity::Progress
+        } else {
+            Verbosity::Quiet
+        },
+        ..Driver::default()
+    };
 
-fn arrangements(line: &str) -> usize {
-    // eprintln!("{}", line.blue());
-    let (record, groups) = parse_row(line);
+    println!("Arrangement count: {}", total(&driver.run(&lines)));
+    // 7857
 
-    let result = try_match(record.into_iter(), groups.into_iter(), &[]);
-    // eprintln!("{}", result.red());
-    result
+    let driver = Driver {
+        unfold: 5,
+        ..driver
+    };
+    println!("Arrangement count x5: {}", total(&driver.run(&lines)));
+    // 28606137449920
 }
 
-fn arrangements_x5(line: &str) -> usize {
-    eprintln!("{}", line.blue());
-    let (record, groups) = parse_row(line);
-
-    let record = record
+fn total(counts: &[Option<u128>]) -> String {
+    counts
         .iter()
-        .copied()
-        .chain(once(State::Unknown))
-        .chain(record.iter().copied())
-        .chain(once(State::Unknown))
-        .chain(record.iter().copied())
-        .chain(once(State::Unknown))
-        .chain(record.iter().copied())
-        .chain(once(State::Unknown))
-        .chain(record.iter().copied());
-
-    let result = try_match(record, (0..5).flat_map(|_| groups.iter().copied()), &[]);
-    eprintln!("{}", result.red());
-    result
+        .try_fold(0u128, |total, count| total.checked_add((*count)?))
+        .map_or_else(
+            || "too many to count".to_string(),
+            |total| total.to_string(),
+        )
 }
+
 #[cfg(test)]
 mod tests {
+    use day_12::Driver;
 
     const RECORDS: &[&str] = &[
         "???.### 1,1,3",
@@ -58,27 +51,27 @@ mod tests {
 
     #[test]
     fn operational_arrangements() {
-        let expected = [1, 4, 1, 1, 4, 10];
-
-        for (line, expected) in RECORDS.iter().zip(expected.iter()) {
-            let got = super::arrangements(line);
-            assert_eq!(got, *expected, "{line}: expected {expected}, got {got}");
-        }
+        let expected = [1, 4, 1, 1, 4, 10].map(Some);
+        assert_eq!(Driver::default().run(RECORDS), expected);
     }
 
     #[test]
     fn operational_arrangements_x5() {
-        let expected = [1, 16384, 1, 16, 2500, 506250];
-
-        for (line, expected) in RECORDS.iter().zip(expected.iter()) {
-            let got = super::arrangements_x5(line);
-            assert_eq!(got, *expected, "{line}: expected {expected}, got {got}");
-        }
+        let expected = [1, 16384, 1, 16, 2500, 506250].map(Some);
+        let driver = Driver {
+            unfold: 5,
+            ..Driver::default()
+        };
+        assert_eq!(driver.run(RECORDS), expected);
     }
 
     #[test]
-    #[ignore = "too slow"]
     fn too_slow() {
-        super::arrangements_x5("?????#???????????#?# 1,4,1,2,1,1");
+        let driver = Driver {
+            unfold: 5,
+            ..Driver::default()
+        };
+        let got = driver.run(&["?????#???????????#?# 1,4,1,2,1,1"]);
+        assert_eq!(got, [Some(80820436091)]);
     }
 }
@@ -0,0 +1,326 @@
+use std::fmt::Display;
+
+use crate::{Counter, State};
+
+/// Cells of `line` that are the same in every arrangement matching `groups`, with the
+/// others left unknown. `None` if nothing matches. A clue of `[0]` is an empty line.
+pub fn solve_line(line: &[State], groups: &[usize]) -> Option<Vec<State>> {
+    let groups = if groups == [0] { &[] } else { groups };
+    let counter = Counter::new(line, groups);
+    if counter.count(0, 0, 0) == Some(0) {
+        return None;
+    }
+
+    // (group, run) states reachable at each position that still have a completion
+    let max_run = groups.iter().copied().max().unwrap_or_default();
+    let key = |(group, run): (usize, usize)| group * (max_run + 1) + run;
+    let mut reachable = vec![false; (groups.len() + 1) * (max_run + 1)];
+    reachable[0] = true;
+
+    let mut solved = Vec::with_capacity(line.len());
+    for pos in 0..line.len() {
+        let mut next_reachable = vec![false; reachable.len()];
+        let (mut operational, mut damaged) = (false, false);
+        for group in 0..=groups.len() {
+            for run in 0..=max_run {
+                if !reachable[key((group, run))] {
+                    continue;
+                }
+                for state in [State::Operational, State::Damaged] {
+                    let Some(next) = counter.step(pos, group, run, state) else {
+                        continue;
+                    };
+                    if counter.count(pos + 1, next.0, next.1) != Some(0) {
+                        next_reachable[key(next)] = true;
+                        match state {
+                            State::Operational => operational = true,
+                            _ => damaged = true,
+                        }
+                    }
+                }
+            }
+        }
+        solved.push(match (operational, damaged) {
+            (true, false) => State::Operational,
+            (false, true) => State::Damaged,
+            _ => State::Unknown,
+        });
+        reachable = next_reachable;
+    }
+    Some(solved)
+}
+
+/// Cells row by row, damaged springs standing for filled cells.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid(pub Vec<Vec<State>>);
+
+impl Grid {
+    pub fn is_solved(&self) -> bool {
+        self.0
+            .iter()
+            .flatten()
+            .all(|&state| state != State::Unknown)
+    }
+}
+
+impl Display for Grid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in &self.0 {
+            for state in row {
+                write!(f, "{state}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Solution {
+    Unsolvable,
+    Unique(Grid),
+    /// two of the solutions
+    Multiple(Grid, Grid),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClueError {
+    /// row `y` needs more cells than there are columns
+    RowTooLong(usize),
+    /// column `x` needs more cells than there are rows
+    ColumnTooLong(usize),
+    /// row `y` has an empty group beside other groups
+    RowZeroGroup(usize),
+    /// column `x` has an empty group beside other groups
+    ColumnZeroGroup(usize),
+    /// the rows and columns fill different numbers of cells
+    FilledCells { rows: usize, columns: usize },
+}
+
+impl Display for ClueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClueError::RowTooLong(y) => write!(f, "row {y} does not fit in the grid"),
+            ClueError::ColumnTooLong(x) => write!(f, "column {x} does not fit in the grid"),
+            ClueError::RowZeroGroup(y) => write!(f, "row {y} has a group of 0 among others"),
+            ClueError::ColumnZeroGroup(x) => {
+                write!(f, "column {x} has a group of 0 among others")
+            }
+            ClueError::FilledCells { rows, columns } => {
+                write!(f, "rows fill {rows} cells but columns fill {columns}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClueError {}
+
+/// A picture puzzle where every row and column is a spring record with nothing known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Nonogram {
+    pub rows: Vec<Vec<usize>>,
+    pub columns: Vec<Vec<usize>>,
+}
+
+impl Nonogram {
+    /// Clues of `[0]` become empty lines, any other group of 0 is an error.
+    pub fn new(mut rows: Vec<Vec<usize>>, mut columns: Vec<Vec<usize>>) -> Result<Self, ClueError> {
+        for clue in rows.iter_mut().chain(&mut columns) {
+            if *clue == [0] {
+                clue.clear();
+            }
+        }
+        if let Some(y) = rows.iter().position(|clue| clue.contains(&0)) {
+            return Err(ClueError::RowZeroGroup(y));
+        }
+        if let Some(x) = columns.iter().position(|clue| clue.contains(&0)) {
+            return Err(ClueError::ColumnZeroGroup(x));
+        }
+
+        // the groups and a gap between each pair
+        let span = |clue: &Vec<usize>| clue.iter().sum::<usize>() + clue.len().saturating_sub(1);
+        if let Some(y) = rows.iter().position(|clue| span(clue) > columns.len()) {
+            return Err(ClueError::RowTooLong(y));
+        }
+        if let Some(x) = columns.iter().position(|clue| span(clue) > rows.len()) {
+            return Err(ClueError::ColumnTooLong(x));
+        }
+        let filled = |clues: &[Vec<usize>]| clues.iter().flatten().sum::<usize>();
+        if filled(&rows) != filled(&columns) {
+            return Err(ClueError::FilledCells {
+                rows: filled(&rows),
+                columns: filled(&columns),
+            });
+        }
+        Ok(Self { rows, columns })
+    }
+
+    /// Everything line solving alone can fill in, `None` on a contradiction.
+    pub fn line_solve(&self) -> Option<Grid> {
+        let grid = Grid(vec![
+            vec![State::Unknown; self.columns.len()];
+            self.rows.len()
+        ]);
+        self.propagate(grid)
+    }
+
+    /// Solves rows and columns in turn until nothing changes.
+    fn propagate(&self, mut grid: Grid) -> Option<Grid> {
+        loop {
+            let mut changed = false;
+            for (y, groups) in self.rows.iter().enumerate() {
+                let solved = solve_line(&grid.0[y], groups)?;
+                changed |= solved != grid.0[y];
+                grid.0[y] = solved;
+            }
+            for (x, groups) in self.columns.iter().enumerate() {
+                let column = grid.0.iter().map(|row| row[x]).collect::<Vec<_>>();
+                let solved = solve_line(&column, groups)?;
+                for (row, state) in grid.0.iter_mut().zip(solved) {
+                    changed |= row[x] != state;
+                    row[x] = state;
+                }
+            }
+            if !changed {
+                return Some(grid);
+            }
+        }
+    }
+
+    /// Line solving, guessing the first unknown cell whenever it gets stuck.
+    pub fn solve(&self) -> Solution {
+        let mut found = Vec::new();
+        if let Some(grid) = self.line_solve() {
+            self.search(grid, &mut found);
+        }
+        let mut found = found.into_iter();
+        match (found.next(), found.next()) {
+            (None, _) => Solution::Unsolvable,
+            (Some(grid), None) => Solution::Unique(grid),
+            (Some(first), Some(second)) => Solution::Multiple(first, second),
+        }
+    }
+
+    /// Collects up to two solutions extending `grid`.
+    fn search(&self, grid: Grid, found: &mut Vec<Grid>) {
+        let unknown = grid.0.iter().enumerate().find_map(|(y, row)| {
+            let x = row.iter().position(|&state| state == State::Unknown)?;
+            Some((y, x))
+        });
+        let Some((y, x)) = unknown else {
+            found.push(grid);
+            return;
+        };
+
+        for guess in [State::Damaged, State::Operational] {
+            if found.len() >= 2 {
+                return;
+            }
+            let mut guessed = grid.clone();
+            guessed.0[y][x] = guess;
+            if let Some(guessed) = self.propagate(guessed) {
+                self.search(guessed, found);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{solve_line, ClueError, Nonogram, Solution};
+    use crate::{parse_row, State};
+
+    fn line(line: &str) -> Vec<State> {
+        parse_row(&format!("{line} 0")).0
+    }
+
+    #[test]
+    fn line_solving() {
+        let solved = solve_line(&line("?????"), &[3]).unwrap();
+        assert_eq!(solved, line("??#??"));
+        let solved = solve_line(&line("?#???"), &[3, 1]).unwrap();
+        assert_eq!(solved, line("###.#"));
+        let solved = solve_line(&line("??????"), &[1, 1]).unwrap();
+        assert_eq!(solved, line("??????"));
+        assert_eq!(solve_line(&line("#????"), &[]), None);
+        assert_eq!(solve_line(&line("....."), &[]).unwrap(), line("....."));
+        assert_eq!(solve_line(&line("?????"), &[0]).unwrap(), line("....."));
+    }
+
+    #[test]
+    fn unique() {
+        let nonogram = Nonogram::new(
+            vec![vec![1, 1], vec![5], vec![5], vec![3], vec![1]],
+            vec![vec![2], vec![4], vec![4], vec![4], vec![2]],
+        )
+        .unwrap();
+        let Solution::Unique(grid) = nonogram.solve() else {
+            panic!("expected a unique solution");
+        };
+        assert!(grid.is_solved());
+        assert_eq!(
+            grid.to_string(),
+            concat! {
+                ".#.#.\n",
+                "#####\n",
+                "#####\n",
+                ".###.\n",
+                "..#..\n",
+            }
+        );
+    }
+
+    #[test]
+    fn multiple_and_unsolvable() {
+        // either diagonal, which line solving alone cannot decide
+        let nonogram = Nonogram::new(vec![vec![1], vec![1]], vec![vec![1], vec![1]]).unwrap();
+        assert_eq!(nonogram.line_solve().unwrap().to_string(), "??\n??\n");
+        let Solution::Multiple(first, second) = nonogram.solve() else {
+            panic!("expected several solutions");
+        };
+        assert_eq!(first.to_string(), "#.\n.#\n");
+        assert_eq!(second.to_string(), ".#\n#.\n");
+
+        // consistent clues that no grid satisfies
+        let nonogram = Nonogram::new(
+            vec![vec![1, 1], vec![0], vec![1]],
+            vec![vec![2], vec![0], vec![1]],
+        )
+        .unwrap();
+        assert_eq!(nonogram.solve(), Solution::Unsolvable);
+    }
+
+    #[test]
+    fn clues() {
+        let nonogram = Nonogram::new(vec![vec![0], vec![1]], vec![vec![1], vec![0]]).unwrap();
+        assert_eq!(nonogram.rows, [vec![], vec![1]]);
+        let Solution::Unique(grid) = nonogram.solve() else {
+            panic!("expected a unique solution");
+        };
+        assert_eq!(grid.to_string(), "..\n#.\n");
+
+        assert_eq!(
+            Nonogram::new(vec![vec![1, 1]], vec![vec![1], vec![1]]),
+            Err(ClueError::RowTooLong(0))
+        );
+        assert_eq!(
+            Nonogram::new(vec![vec![1]], vec![vec![2]]),
+            Err(ClueError::ColumnTooLong(0))
+        );
+        assert_eq!(
+            Nonogram::new(vec![vec![1, 0, 2], vec![]], vec![vec![1]; 4]),
+            Err(ClueError::RowZeroGroup(0))
+        );
+        assert_eq!(
+            Nonogram::new(vec![vec![1]; 2], vec![vec![0], vec![0, 2]]),
+            Err(ClueError::ColumnZeroGroup(1))
+        );
+        assert_eq!(
+            Nonogram::new(vec![vec![2], vec![]], vec![vec![1], vec![]]),
+            Err(ClueError::FilledCells {
+                rows: 2,
+                columns: 1
+            })
+        );
+    }
+}
@@ -0,0 +1,311 @@
+use std::fmt::Display;
+
+mod symmetry;
+
+pub use symmetry::{analyse, Analysis, Region, Symmetry};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// no rows, or rows with no pixels
+    Empty,
+    /// `row` is `len` pixels wide where the first row is `width`
+    Ragged {
+        row: usize,
+        width: usize,
+        len: usize,
+    },
+    UnknownPixel {
+        row: usize,
+        column: usize,
+        c: char,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Empty => write!(f, "empty pattern"),
+            Error::Ragged { row, width, len } => {
+                write!(f, "row {row} is {len} wide, expected {width}")
+            }
+            Error::UnknownPixel { row, column, c } => {
+                write!(f, "unknown pixel {c:?} at row {row}, column {column}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Pixel {
+    Ash,
+    Rocks,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Axis {
+    /// between rows `y - 1` and `y`
+    Horizontal(usize),
+    /// between columns `x - 1` and `x`
+    Vertical(usize),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Reflection {
+    pub axis: Axis,
+    /// `(x, y)` of every cell above or left of the axis that differs from its mirror image
+    pub smudges: Vec<(usize, usize)>,
+}
+
+/// Bits in each word of a [`Pattern`] line.
+const WORD: usize = u64::BITS as usize;
+
+/// A rectangle of pixels, kept as bitsets of `u64` words with bit `x` of a row and bit
+/// `y` of a column set for rocks.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Pattern {
+    rows: Vec<Vec<u64>>,
+    columns: Vec<Vec<u64>>,
+}
+
+impl Pattern {
+    pub fn parse(block: &str) -> Result<Self, Error> {
+        let lines = block.lines().collect::<Vec<_>>();
+        let width = lines.first().map_or(0, |line| line.chars().count());
+        if width == 0 {
+            return Err(Error::Empty);
+        }
+
+        let mut rows = vec![vec![0; width.div_ceil(WORD)]; lines.len()];
+        let mut columns = vec![vec![0; lines.len().div_ceil(WORD)]; width];
+        for (y, line) in lines.iter().enumerate() {
+            let len = line.chars().count();
+            if len != width {
+                return Err(Error::Ragged { row: y, width, len });
+            }
+            for (x, c) in line.chars().enumerate() {
+                match c {
+                    '.' => (),
+                    '#' => {
+                        rows[y][x / WORD] |= 1 << (x % WORD);
+                        columns[x][y / WORD] |= 1 << (y % WORD);
+                    }
+                    c => {
+                        return Err(Error::UnknownPixel {
+                            row: y,
+                            column: x,
+                            c,
+                        })
+                    }
+                }
+            }
+        }
+        Ok(Self { rows, columns })
+    }
+
+    pub fn width(&self) -> usize {
+        self.columns.len()
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> Pixel {
+        if self.rows[y][x / WORD] >> (x % WORD) & 1 == 1 {
+            Pixel::Rocks
+        } else {
+            Pixel::Ash
+        }
+    }
+
+    /// 100 times the rows above the first horizontal reflection with `smudges`
+    /// differences, or the columns left of the first vertical one.
+    pub fn summary(&self, smudges: usize) -> Option<usize> {
+        let reflection = find_reflections(self, smudges).into_iter().next()?;
+        Some(match reflection.axis {
+            Axis::Horizontal(y) => y * 100,
+            Axis::Vertical(x) => x,
+        })
+    }
+}
+
+/// Every blank-line separated block of `input`, parsed on its own.
+pub fn parse_patterns(input: &str) -> impl Iterator<Item = Result<Pattern, Error>> + '_ {
+    input.split("\n\n").map(Pattern::parse)
+}
+
+/// Every mirror line, horizontal ones first, across which exactly `smudges` cells differ.
+pub fn find_reflections(pattern: &Pattern, smudges: usize) -> Vec<Reflection> {
+    let horizontal = mirror_lines(&pattern.rows, smudges).map(|(y, differences)| Reflection {
+        axis: Axis::Horizontal(y),
+        smudges: differences,
+    });
+    let vertical = mirror_lines(&pattern.columns, smudges).map(|(x, differences)| Reflection {
+        axis: Axis::Vertical(x),
+        smudges: differences.into_iter().map(|(y, x)| (x, y)).collect(),
+    });
+    horizontal.chain(vertical).collect()
+}
+
+/// Mirror lines between `lines` with exactly `smudges` differing bits, along with
+/// `(bit, line)` of each difference on the near side.
+fn mirror_lines(
+    lines: &[Vec<u64>],
+    smudges: usize,
+) -> impl Iterator<Item = (usize, Vec<(usize, usize)>)> + '_ {
+    let diff = |near: usize, far: usize| {
+        lines[near]
+            .iter()
+            .zip(&lines[far])
+            .map(|(near, far)| near ^ far)
+    };
+    (1..lines.len()).filter_map(move |axis| {
+        let pairs = (0..axis).rev().zip(axis..lines.len());
+        let mut differences = 0;
+        for (near, far) in pairs.clone() {
+            differences += diff(near, far)
+                .map(|word| word.count_ones() as usize)
+                .sum::<usize>();
+            if differences > smudges {
+                return None;
+            }
+        }
+        if differences != smudges {
+            return None;
+        }
+
+        let cells = pairs
+            .flat_map(|(near, far)| {
+                diff(near, far).enumerate().flat_map(move |(i, word)| {
+                    (0..WORD)
+                        .filter(move |bit| word >> bit & 1 == 1)
+                        .map(move |bit| (i * WORD + bit, near))
+                })
+            })
+            .collect();
+        Some((axis, cells))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_patterns, Axis, Error, Pattern, Pixel, Reflection};
+
+    pub const PATTERNS: &str = concat! {
+        "#.##..##.\n",
+        "..#.##.#.\n",
+        "##......#\n",
+        "##......#\n",
+        "..#.##.#.\n",
+        "..##..##.\n",
+        "#.#.##.#.\n",
+        "\n",
+        "#...##..#\n",
+        "#....#..#\n",
+        "..##..###\n",
+        "#####.##.\n",
+        "#####.##.\n",
+        "..##..###\n",
+        "#....#..#\n",
+    };
+
+    #[test]
+    fn find_reflections() {
+        let patterns = parse_patterns(PATTERNS)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(
+            super::find_reflections(&patterns[0], 0),
+            [Reflection {
+                axis: Axis::Vertical(5),
+                smudges: vec![],
+            }]
+        );
+        assert_eq!(
+            super::find_reflections(&patterns[0], 1),
+            [Reflection {
+                axis: Axis::Horizontal(3),
+                smudges: vec![(0, 0)],
+            }]
+        );
+        assert_eq!(
+            super::find_reflections(&patterns[1], 1),
+            [Reflection {
+                axis: Axis::Horizontal(1),
+                smudges: vec![(4, 0)],
+            }]
+        );
+        // every difference is on the near side of the line
+        for pattern in &patterns {
+            for smudges in 0..6 {
+                for reflection in super::find_reflections(pattern, smudges) {
+                    assert_eq!(reflection.smudges.len(), smudges);
+                    for (x, y) in reflection.smudges {
+                        match reflection.axis {
+                            Axis::Horizontal(axis) => assert!(y < axis),
+                            Axis::Vertical(axis) => assert!(x < axis),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parse() {
+        let pattern = Pattern::parse("#.#\n..#\n").unwrap();
+        assert_eq!((pattern.width(), pattern.height()), (3, 2));
+        assert_eq!(pattern.pixel(0, 0), Pixel::Rocks);
+        assert_eq!(pattern.pixel(0, 1), Pixel::Ash);
+
+        assert_eq!(Pattern::parse(""), Err(Error::Empty));
+        assert_eq!(Pattern::parse("\n"), Err(Error::Empty));
+        assert_eq!(
+            Pattern::parse("#.#\n.#\n"),
+            Err(Error::Ragged {
+                row: 1,
+                width: 3,
+                len: 2,
+            })
+        );
+        assert_eq!(
+            Pattern::parse("#.#\n.o.\n"),
+            Err(Error::UnknownPixel {
+                row: 1,
+                column: 1,
+                c: 'o',
+            })
+        );
+
+        let pattern = Pattern::parse(&format!("{}#\n", ".".repeat(64))).unwrap();
+        assert_eq!((pattern.width(), pattern.height()), (65, 1));
+        assert_eq!(pattern.pixel(63, 0), Pixel::Ash);
+        assert_eq!(pattern.pixel(64, 0), Pixel::Rocks);
+    }
+
+    #[test]
+    fn wide_reflections() {
+        // mirrored across column 66, with the cell at (64, 0) smudged
+        let pattern = Pattern::parse(&format!(
+            "#{0}#..#\n##{1}.##.\n",
+            ".".repeat(63),
+            ".".repeat(62)
+        ))
+        .unwrap();
+        assert_eq!(pattern.summary(0), Some(66));
+        let smudged = Pattern::parse(&format!(
+            "#{0}...#\n##{1}.##.\n",
+            ".".repeat(63),
+            ".".repeat(62)
+        ))
+        .unwrap();
+        // the first two columns also differ in one cell
+        assert!(super::find_reflections(&smudged, 1).contains(&Reflection {
+            axis: Axis::Vertical(66),
+            smudges: vec![(64, 0)],
+        }));
+    }
+}
@@ -1,143 +1,81 @@
+use day_13::{analyse, parse_patterns, Error, Pattern, Symmetry};
+
 const INPUT: &str = include_str!("input.txt");
 
 fn main() {
-    println!("Reflections: {}", reflection(INPUT));
-    println!("Smudged: {}", reflection_smudged(INPUT));
-}
+    let patterns = parse_patterns(INPUT).collect::<Vec<_>>();
+    for (pattern_i, pattern) in patterns.iter().enumerate() {
+        if let Err(err) = pattern {
+            eprintln!("pattern {pattern_i}: {err}");
+        }
+    }
 
-#[derive(PartialEq, Eq)]
-enum Pixel {
-    Ash,
-    Rocks,
+    println!("Reflections: {}", reflection(&patterns));
+    println!("Smudged: {}", reflection_smudged(&patterns));
+
+    let analyses = patterns.iter().flatten().map(analyse).collect::<Vec<_>>();
+    let counts = [
+        Symmetry::Horizontal,
+        Symmetry::Vertical,
+        Symmetry::HalfTurn,
+        Symmetry::QuarterTurn,
+        Symmetry::Diagonal,
+        Symmetry::AntiDiagonal,
+    ]
+    .map(|symmetry| {
+        let count = analyses
+            .iter()
+            .filter(|analysis| analysis.symmetries.contains(&symmetry))
+            .count();
+        format!("{symmetry} {count}")
+    });
+    println!("Symmetric patterns: {}", counts.join(", "));
+    let largest = analyses
+        .iter()
+        .filter_map(|analysis| analysis.largest.as_ref())
+        .max_by_key(|(region, _)| region.area());
+    if let Some((region, symmetries)) = largest {
+        let symmetries = symmetries
+            .iter()
+            .map(Symmetry::to_string)
+            .collect::<Vec<_>>();
+        println!(
+            "Largest symmetric region: {}x{} ({})",
+            region.width,
+            region.height,
+            symmetries.join(", ")
+        );
+    }
 }
 
-fn reflection(patterns: &str) -> usize {
-    patterns
-        .split("\n\n")
-        .enumerate()
-        .map(|(pattern_i, pattern)| {
-            let pattern = pattern
-                .lines()
-                .map(|line| {
-                    line.chars()
-                        .map(|c| match c {
-                            '.' => Pixel::Ash,
-                            '#' => Pixel::Rocks,
-                            c => panic!("{c:?}"),
-                        })
-                        .collect::<Vec<_>>()
-                })
-                .collect::<Vec<_>>();
-
-            // horizontal
-            for reflected_y in 1..pattern.len() {
-                // eprintln!("does {reflected_y} reflect?");
-                if (0..reflected_y)
-                    .rev()
-                    .zip(reflected_y..pattern.len())
-                    .all(|(y0, y1)| {
-                        // eprintln!("checking {y0} == {y1}");
-                        pattern[y0] == pattern[y1]
-                    })
-                {
-                    // eprintln!("horizontal reflection after {}!", reflected_y);
-                    return reflected_y * 100;
-                }
-            }
-
-            // vertical
-            for reflected_x in 1..pattern[0].len() {
-                if (0..reflected_x)
-                    .rev()
-                    .zip(reflected_x..pattern[0].len())
-                    .all(|(y0, y1)| pattern.iter().all(|row| row[y0] == row[y1]))
-                {
-                    return reflected_x;
-                }
-            }
+fn reflection(patterns: &[Result<Pattern, Error>]) -> usize {
+    summarise(patterns, 0)
+}
 
-            panic!("pattern {pattern_i} has no reflection!")
-        })
-        .sum()
+fn reflection_smudged(patterns: &[Result<Pattern, Error>]) -> usize {
+    summarise(patterns, 1)
 }
 
-fn reflection_smudged(patterns: &str) -> usize {
+/// Summaries of the patterns with a reflection, summed. Patterns that failed to parse are
+/// skipped and ones without a reflection reported.
+fn summarise(patterns: &[Result<Pattern, Error>], smudges: usize) -> usize {
     patterns
-        .split("\n\n")
+        .iter()
         .enumerate()
-        .map(|(pattern_i, pattern)| {
-            let pattern = pattern
-                .lines()
-                .map(|line| {
-                    line.chars()
-                        .map(|c| match c {
-                            '.' => Pixel::Ash,
-                            '#' => Pixel::Rocks,
-                            c => panic!("{c:?}"),
-                        })
-                        .collect::<Vec<_>>()
-                })
-                .collect::<Vec<_>>();
-
-            // horizontal
-            for reflected_y in 1..pattern.len() {
-                let mut smudge_found = false;
-                // eprintln!("does {reflected_y} reflect?");
-                if (0..reflected_y)
-                    .rev()
-                    .zip(reflected_y..pattern.len())
-                    .all(|(y0, y1)| {
-                        let difference = pattern[y0]
-                            .iter()
-                            .zip(&pattern[y1])
-                            .filter(|(p0, p1)| p0 != p1)
-                            .count();
-
-                        // eprintln!("checking {y0} == {y1}: {difference}; {smudge_found}");
-
-                        if !smudge_found && difference == 1 {
-                            smudge_found = true;
-                            true
-                        } else {
-                            difference == 0
-                        }
-                    })
-                    && smudge_found
-                {
-                    // eprintln!("horizontal reflection after {}!", reflected_y);
-                    return reflected_y * 100;
-                }
+        .filter_map(|(pattern_i, pattern)| {
+            let summary = pattern.as_ref().ok()?.summary(smudges);
+            if summary.is_none() {
+                eprintln!("pattern {pattern_i} has no reflection with {smudges} smudges");
             }
-
-            // vertical
-            for reflected_x in 1..pattern[0].len() {
-                let mut smudge_found = false;
-                if (0..reflected_x)
-                    .rev()
-                    .zip(reflected_x..pattern[0].len())
-                    .all(|(y0, y1)| {
-                        let difference = pattern.iter().filter(|row| row[y0] != row[y1]).count();
-
-                        if !smudge_found && difference == 1 {
-                            smudge_found = true;
-                            true
-                        } else {
-                            difference == 0
-                        }
-                    })
-                    && smudge_found
-                {
-                    return reflected_x;
-                }
-            }
-
-            panic!("pattern {pattern_i} has no reflection!")
+            summary
         })
         .sum()
 }
 
 #[cfg(test)]
 mod tests {
+    use day_13::{parse_patterns, Error};
+
     const PATTERNS: &str = concat! {
         "#.##..##.\n",
         "..#.##.#.\n",
@@ -158,13 +96,46 @@ mod tests {
 
     #[test]
     fn reflection() {
-        let result = super::reflection(PATTERNS);
+        let patterns = parse_patterns(PATTERNS).collect::<Vec<_>>();
+        let result = super::reflection(&patterns);
         assert_eq!(result, 405);
     }
 
     #[test]
     fn reflection_smudged() {
-        let result = super::reflection_smudged(PATTERNS);
+        let patterns = parse_patterns(PATTERNS).collect::<Vec<_>>();
+        let result = super::reflection_smudged(&patterns);
         assert_eq!(result, 400);
     }
+
+    #[test]
+    fn bad_patterns() {
+        let input = format!("{PATTERNS}\n#.#\n.#\n\n\n\n#.\n.#\n");
+        let patterns = parse_patterns(&input).collect::<Vec<_>>();
+        assert_eq!(patterns.len(), 5);
+        assert!(patterns[2].is_err());
+        assert_eq!(patterns[3], Err(Error::Empty));
+        // the last has no reflection, so only the good ones count
+        assert_eq!(super::reflection(&patterns), 405);
+        assert_eq!(super::reflection_smudged(&patterns), 400);
+    }
+
+    #[test]
+    fn large_and_ragged_patterns() {
+        // a pattern wider than one word mirrored across column 66, and a row longer than
+        // the first
+        let wide = format!("#{0}#..#\n##{1}.##.\n", ".".repeat(63), ".".repeat(62));
+        let input = format!("{PATTERNS}\n{wide}\n#.\n#.#\n");
+        let patterns = parse_patterns(&input).collect::<Vec<_>>();
+        assert_eq!(patterns[2].as_ref().map(|pattern| pattern.width()), Ok(68));
+        assert_eq!(
+            patterns[3],
+            Err(Error::Ragged {
+                row: 1,
+                width: 2,
+                len: 3
+            })
+        );
+        assert_eq!(super::reflection(&patterns), 405 + 66);
+    }
 }
@@ -0,0 +1,318 @@
+use std::{cmp::Reverse, fmt::Display};
+
+use crate::{Pattern, WORD};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Symmetry {
+    /// top half mirrors the bottom half
+    Horizontal,
+    /// left half mirrors the right half
+    Vertical,
+    /// unchanged by a half turn
+    HalfTurn,
+    /// unchanged by a quarter turn, square regions only
+    QuarterTurn,
+    /// mirrored across the top-left to bottom-right diagonal, square regions only
+    Diagonal,
+    /// mirrored across the top-right to bottom-left diagonal, square regions only
+    AntiDiagonal,
+}
+
+impl Display for Symmetry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Symmetry::Horizontal => "horizontal",
+            Symmetry::Vertical => "vertical",
+            Symmetry::HalfTurn => "half turn",
+            Symmetry::QuarterTurn => "quarter turn",
+            Symmetry::Diagonal => "diagonal",
+            Symmetry::AntiDiagonal => "anti-diagonal",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Region {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Region {
+    pub fn area(&self) -> usize {
+        self.width * self.height
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Analysis {
+    /// symmetries of the whole pattern
+    pub symmetries: Vec<Symmetry>,
+    /// largest square at least 2 wide that a quarter turn leaves unchanged
+    pub quarter_turn: Option<Region>,
+    /// largest region with any symmetry, the topmost, then leftmost, then tallest on a tie,
+    /// and its symmetries
+    pub largest: Option<(Region, Vec<Symmetry>)>,
+}
+
+/// Symmetries of `pattern`, of its square regions and of its largest symmetric region.
+pub fn analyse(pattern: &Pattern) -> Analysis {
+    let (rows, columns) = (&pattern.rows, &pattern.columns);
+    let (height, width) = (pattern.height(), pattern.width());
+    let whole = Region {
+        x: 0,
+        y: 0,
+        width,
+        height,
+    };
+
+    let quarter_turn = (2..=width.min(height)).rev().find_map(|size| {
+        regions(width, height, size, size)
+            .find(|&region| symmetries(rows, columns, region).contains(&Symmetry::QuarterTurn))
+    });
+
+    // taller shapes first, each placed topmost then leftmost, stopping once nothing left
+    // can match the best so far
+    let mut largest: Option<(Region, Vec<Symmetry>)> = None;
+    let rank = |region: &Region| (region.area(), Reverse((region.y, region.x)));
+    for region_height in (1..=height).rev() {
+        for region_width in (1..=width).rev() {
+            let area = region_width * region_height;
+            if largest.as_ref().is_some_and(|(best, _)| best.area() > area) {
+                break;
+            }
+            let found = regions(width, height, region_width, region_height)
+                .map(|region| (region, symmetries(rows, columns, region)))
+                .find(|(_, symmetries)| !symmetries.is_empty());
+            if let Some((region, found)) = found {
+                if largest
+                    .as_ref()
+                    .is_none_or(|(best, _)| rank(&region) > rank(best))
+                {
+                    largest = Some((region, found));
+                }
+            }
+        }
+    }
+
+    Analysis {
+        symmetries: symmetries(rows, columns, whole),
+        quarter_turn,
+        largest,
+    }
+}
+
+/// Every placement of a `region_width` by `region_height` region, row by row.
+fn regions(
+    width: usize,
+    height: usize,
+    region_width: usize,
+    region_height: usize,
+) -> impl Iterator<Item = Region> {
+    (0..=height - region_height).flat_map(move |y| {
+        (0..=width - region_width).map(move |x| Region {
+            x,
+            y,
+            width: region_width,
+            height: region_height,
+        })
+    })
+}
+
+/// `1..=64` bits of `line` from bit `start`, as one word.
+fn word(line: &[u64], start: usize, len: usize) -> u64 {
+    let (i, shift) = (start / WORD, start % WORD);
+    let mut word = line[i] >> shift;
+    if shift > 0 && i + 1 < line.len() {
+        word |= line[i + 1] << (WORD - shift);
+    }
+    word & (u64::MAX >> (WORD - len))
+}
+
+/// `len` bits of `line` from bit `start`.
+fn bits(line: &[u64], start: usize, len: usize) -> Vec<u64> {
+    (0..len.div_ceil(WORD))
+        .map(|i| word(line, start + i * WORD, (len - i * WORD).min(WORD)))
+        .collect()
+}
+
+/// The `len` low bits of `line` in reverse order.
+fn reverse(line: &[u64], len: usize) -> Vec<u64> {
+    (0..len.div_ceil(WORD))
+        .map(|i| {
+            // word `i` holds the reversed bits that end `i` words before `len`
+            let count = (len - i * WORD).min(WORD);
+            word(line, len - i * WORD - count, count).reverse_bits() >> (WORD - count)
+        })
+        .collect()
+}
+
+fn symmetries(rows: &[Vec<u64>], columns: &[Vec<u64>], region: Region) -> Vec<Symmetry> {
+    let Region {
+        x,
+        y,
+        width,
+        height,
+    } = region;
+    if width == 0 || height == 0 {
+        return Vec::new();
+    }
+    // bit j of row i is the cell at (j, i) of the region, bit i of column j the same cell
+    let rows = (y..y + height)
+        .map(|row| bits(&rows[row], x, width))
+        .collect::<Vec<_>>();
+    let columns = (x..x + width)
+        .map(|column| bits(&columns[column], y, height))
+        .collect::<Vec<_>>();
+    let all = |check: &dyn Fn(usize) -> bool| (0..height).all(check);
+
+    let mut found = Vec::new();
+    if all(&|i| rows[i] == rows[height - 1 - i]) {
+        found.push(Symmetry::Horizontal);
+    }
+    if all(&|i| rows[i] == reverse(&rows[i], width)) {
+        found.push(Symmetry::Vertical);
+    }
+    if all(&|i| rows[i] == reverse(&rows[height - 1 - i], width)) {
+        found.push(Symmetry::HalfTurn);
+    }
+    if width == height {
+        let size = width;
+        // a quarter turn takes the cell at (size - 1 - i, j) to (j, i)
+        if all(&|i| rows[i] == reverse(&columns[i], size)) {
+            found.push(Symmetry::QuarterTurn);
+        }
+        if all(&|i| rows[i] == columns[i]) {
+            found.push(Symmetry::Diagonal);
+        }
+        if all(&|i| rows[i] == reverse(&columns[size - 1 - i], size)) {
+            found.push(Symmetry::AntiDiagonal);
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{analyse, bits, reverse, Region, Symmetry};
+    use crate::Pattern;
+
+    fn parse(pattern: &str) -> Pattern {
+        Pattern::parse(pattern).unwrap()
+    }
+
+    #[test]
+    fn whole_pattern() {
+        let analysis = analyse(&parse("#..\n.#.\n..#\n"));
+        assert_eq!(
+            analysis.symmetries,
+            [
+                Symmetry::HalfTurn,
+                Symmetry::Diagonal,
+                Symmetry::AntiDiagonal
+            ]
+        );
+
+        let analysis = analyse(&parse(".#.\n###\n.#.\n"));
+        assert_eq!(
+            analysis.symmetries,
+            [
+                Symmetry::Horizontal,
+                Symmetry::Vertical,
+                Symmetry::HalfTurn,
+                Symmetry::QuarterTurn,
+                Symmetry::Diagonal,
+                Symmetry::AntiDiagonal,
+            ]
+        );
+
+        let analysis = analyse(&parse("##.\n#..\n"));
+        assert!(analysis.symmetries.is_empty());
+    }
+
+    #[test]
+    fn quarter_turn() {
+        // any 2 by 2 block of one kind would do
+        let analysis = analyse(&parse("##.\n.#.\n...\n"));
+        assert_eq!(analysis.quarter_turn, None);
+
+        let pattern = parse(concat! {
+            "#.....\n",
+            "...##.\n",
+            "..#..#\n",
+            "..#..#\n",
+            "...##.\n",
+        });
+        let analysis = analyse(&pattern);
+        assert_eq!(
+            analysis.quarter_turn,
+            Some(Region {
+                x: 2,
+                y: 1,
+                width: 4,
+                height: 4,
+            })
+        );
+    }
+
+    #[test]
+    fn largest() {
+        let pattern = parse(concat! {
+            "#.##..##.\n",
+            "..#.##.#.\n",
+            "##......#\n",
+            "##......#\n",
+            "..#.##.#.\n",
+            "..##..##.\n",
+            "#.#.##.#.\n",
+        });
+        let (region, symmetries) = analyse(&pattern).largest.unwrap();
+        // the vertical reflection between columns 4 and 5
+        assert_eq!(
+            region,
+            Region {
+                x: 1,
+                y: 0,
+                width: 8,
+                height: 7,
+            }
+        );
+        assert_eq!(symmetries, [Symmetry::Vertical]);
+    }
+
+    #[test]
+    fn wide_lines() {
+        // bits 63 and 64 set, either side of a word boundary
+        let line = [1 << 63, 1];
+        assert_eq!(bits(&line, 60, 8), [0b11000]);
+        assert_eq!(bits(&line, 0, 65), line);
+        assert_eq!(reverse(&line, 65), [0b11, 0]);
+        assert_eq!(reverse(&line, 66), [0b110, 0]);
+
+        let row = format!("##.{}.##", ".".repeat(64));
+        let analysis = analyse(&parse(&format!("{row}\n{row}\n")));
+        assert_eq!(
+            analysis.symmetries,
+            [Symmetry::Horizontal, Symmetry::Vertical, Symmetry::HalfTurn]
+        );
+    }
+
+    #[test]
+    fn largest_tie() {
+        // a 2 by 3 region at (1, 0) mirrors top to bottom, a 3 by 2 one at (0, 0) is
+        // unchanged by a half turn
+        let (region, symmetries) = analyse(&parse("##..\n.##.\n.#.#\n")).largest.unwrap();
+        assert_eq!(
+            region,
+            Region {
+                x: 0,
+                y: 0,
+                width: 3,
+                height: 2,
+            }
+        );
+        assert_eq!(symmetries, [Symmetry::HalfTurn]);
+    }
+}
@@ -0,0 +1,323 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use crate::RangeMap;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    NotANumber(String),
+    FieldCount(usize),
+    DuplicateStart {
+        line: usize,
+    },
+    Overlap {
+        line: usize,
+    },
+    Overflow,
+    Gap {
+        start: u64,
+        end: u64,
+    },
+    /// seeds come in (start, length) pairs
+    OddSeedCount(usize),
+    SeedRangeOverflow {
+        start: u64,
+        length: u64,
+    },
+}
+
+impl Issue {
+    /// Gaps are legal, unmapped values keep their number.
+    pub fn is_error(&self) -> bool {
+        !matches!(self, Issue::Gap { .. })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub section: String,
+    pub line: usize,
+    pub issue: Issue,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (line {}): ", self.section, self.line)?;
+        match &self.issue {
+            Issue::NotANumber(field) => write!(f, "{field:?} is not a number"),
+            Issue::FieldCount(count) => write!(f, "expected 3 numbers, found {count}"),
+            Issue::DuplicateStart { line } => write!(f, "same source start as line {line}"),
+            Issue::Overlap { line } => write!(f, "source range overlaps line {line}"),
+            Issue::Overflow => write!(f, "range overflows u64"),
+            Issue::Gap { start, end } => write!(f, "sources {start}..{end} are unmapped"),
+            Issue::OddSeedCount(count) => write!(f, "{count} seeds do not make up pairs"),
+            Issue::SeedRangeOverflow { start, length } => {
+                write!(f, "seed range {start} + {length} overflows u64")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Almanac {
+    pub seeds: Vec<u64>,
+    pub layers: Vec<RangeMap>,
+}
+
+impl Almanac {
+    /// Leaves out malformed lines and ranges that start inside an earlier one, every problem
+    /// found is returned alongside the almanac.
+    pub fn parse(input: &str) -> (Self, Vec<Diagnostic>) {
+        let mut diagnostics = Vec::new();
+        let mut almanac = Almanac::default();
+
+        let mut line = 1;
+        for (i, section) in input.split("\n\n").enumerate() {
+            if i == 0 {
+                let (tag, seeds) = section.split_once(':').unwrap_or(("seeds", section));
+                let diagnostic = |issue| Diagnostic {
+                    section: tag.to_string(),
+                    line,
+                    issue,
+                };
+                let fields = seeds.split_whitespace().collect::<Vec<_>>();
+                for field in &fields {
+                    match field.parse::<u64>() {
+                        Ok(seed) => almanac.seeds.push(seed),
+                        Err(_) => {
+                            diagnostics.push(diagnostic(Issue::NotANumber(field.to_string())))
+                        }
+                    }
+                }
+                if fields.len() % 2 == 1 {
+                    diagnostics.push(diagnostic(Issue::OddSeedCount(fields.len())));
+                }
+                // pairs are only meaningful when every seed parsed
+                if almanac.seeds.len() == fields.len() {
+                    for pair in almanac.seeds.chunks_exact(2) {
+                        let (start, length) = (pair[0], pair[1]);
+                        if start.checked_add(length).is_none() {
+                            diagnostics
+                                .push(diagnostic(Issue::SeedRangeOverflow { start, length }));
+                        }
+                    }
+                }
+            } else {
+                let mapping = check_mapping(section, line, &mut diagnostics);
+                almanac.layers.push(RangeMap::from(mapping));
+            }
+            line += section.split('\n').count() + 1;
+        }
+
+        (almanac, diagnostics)
+    }
+
+    /// Rejects the almanac if any line is malformed, overflows or overlaps another.
+    pub fn parse_strict(input: &str) -> Result<Self, Vec<Diagnostic>> {
+        let (almanac, mut diagnostics) = Self::parse(input);
+        diagnostics.retain(|diagnostic| diagnostic.issue.is_error());
+        if diagnostics.is_empty() {
+            Ok(almanac)
+        } else {
+            Err(diagnostics)
+        }
+    }
+}
+
+pub fn validate(input: &str) -> Vec<Diagnostic> {
+    Almanac::parse(input).1
+}
+
+/// `first_line` is the line number of the section header within the input.
+fn check_mapping(
+    mapping: &str,
+    first_line: usize,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> BTreeMap<u64, (u64, u64)> {
+    let mut lines = mapping.split('\n');
+    let section = lines
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches(':')
+        .trim_end_matches(" map")
+        .to_string();
+    let diagnostic = |line, issue| Diagnostic {
+        section: section.clone(),
+        line,
+        issue,
+    };
+
+    // (source, end, dest, line)
+    let mut ranges = Vec::new();
+    for (line, sect_line) in (first_line + 1..).zip(lines) {
+        if sect_line.trim().is_empty() {
+            continue;
+        }
+
+        let fields = sect_line
+            .split_whitespace()
+            .map(|field| {
+                field
+                    .parse::<u64>()
+                    .map_err(|_| Issue::NotANumber(field.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>();
+        let [dest, source, length] = match fields.as_deref() {
+            Ok(&[dest, source, length]) => [dest, source, length],
+            Ok(fields) => {
+                diagnostics.push(diagnostic(line, Issue::FieldCount(fields.len())));
+                continue;
+            }
+            Err(issue) => {
+                diagnostics.push(diagnostic(line, issue.clone()));
+                continue;
+            }
+        };
+
+        match (source.checked_add(length), dest.checked_add(length)) {
+            (Some(end), Some(_)) => ranges.push((source, end, dest, line)),
+            _ => diagnostics.push(diagnostic(line, Issue::Overflow)),
+        }
+    }
+
+    ranges.sort_by_key(|&(source, _, _, line)| (source, line));
+
+    let mut mapping = BTreeMap::new();
+    // (end, line) of the range reaching furthest so far
+    let mut furthest: Option<(u64, usize)> = None;
+    let mut prev_start: Option<(u64, usize)> = None;
+    for &(source, end, dest, line) in &ranges {
+        let issue = match (prev_start, furthest) {
+            (Some((prev, prev_line)), _) if prev == source => {
+                Some(Issue::DuplicateStart { line: prev_line })
+            }
+            (_, Some((furthest_end, furthest_line))) if source < furthest_end => {
+                Some(Issue::Overlap {
+                    line: furthest_line,
+                })
+            }
+            (_, Some((furthest_end, _))) if furthest_end < source => Some(Issue::Gap {
+                start: furthest_end,
+                end: source,
+            }),
+            _ => None,
+        };
+        if let Some(issue) = issue {
+            let is_error = issue.is_error();
+            diagnostics.push(diagnostic(line, issue));
+            // the range already kept wins
+            if is_error {
+                continue;
+            }
+        }
+
+        if furthest.is_none_or(|(furthest_end, _)| furthest_end < end) {
+            furthest = Some((end, line));
+        }
+        prev_start = Some((source, line));
+        mapping.insert(source, (dest, end - source));
+    }
+    mapping
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate, Almanac, Diagnostic, Issue};
+
+    const INPUT: &str = concat!(
+        "seeds: 1 x 3\n",
+        "\n",
+        "seed-to-soil map:\n",
+        "50 98 2\n",
+        "52 50 48\n",
+        "10 60 5\n",
+        "11 50 1\n",
+        "\n",
+        "soil-to-fertilizer map:\n",
+        "0 15 37 4\n",
+        "0 seven 1\n",
+        "0 18446744073709551615 2\n",
+        "0 100 10\n",
+        "20 200 10",
+    );
+
+    fn diagnostic(section: &str, line: usize, issue: Issue) -> Diagnostic {
+        Diagnostic {
+            section: section.to_string(),
+            line,
+            issue,
+        }
+    }
+
+    #[test]
+    fn validate_reports_context() {
+        let diagnostics = validate(INPUT);
+        assert_eq!(
+            diagnostics,
+            [
+                diagnostic("seeds", 1, Issue::NotANumber("x".to_string())),
+                diagnostic("seeds", 1, Issue::OddSeedCount(3)),
+                diagnostic("seed-to-soil", 7, Issue::DuplicateStart { line: 5 }),
+                diagnostic("seed-to-soil", 6, Issue::Overlap { line: 5 }),
+                diagnostic("soil-to-fertilizer", 10, Issue::FieldCount(4)),
+                diagnostic(
+                    "soil-to-fertilizer",
+                    11,
+                    Issue::NotANumber("seven".to_string())
+                ),
+                diagnostic("soil-to-fertilizer", 12, Issue::Overflow),
+                diagnostic(
+                    "soil-to-fertilizer",
+                    14,
+                    Issue::Gap {
+                        start: 110,
+                        end: 200
+                    }
+                ),
+            ]
+        );
+        assert_eq!(
+            diagnostics[3].to_string(),
+            "seed-to-soil (line 6): source range overlaps line 5"
+        );
+    }
+
+    #[test]
+    fn seed_ranges() {
+        let max = u64::MAX;
+        let diagnostics = validate(&format!("seeds: 79 14 {max} 2 5\n\nmap:\n0 0 1"));
+        assert_eq!(
+            diagnostics,
+            [
+                diagnostic("seeds", 1, Issue::OddSeedCount(5)),
+                diagnostic(
+                    "seeds",
+                    1,
+                    Issue::SeedRangeOverflow {
+                        start: max,
+                        length: 2
+                    }
+                ),
+            ]
+        );
+        assert!(validate("seeds: 79 14 55 13\n\nmap:\n0 0 1").is_empty());
+    }
+
+    #[test]
+    fn strict() {
+        let errors = Almanac::parse_strict(INPUT).unwrap_err();
+        assert_eq!(errors.len(), 7);
+
+        let (almanac, _) = Almanac::parse(INPUT);
+        assert_eq!(almanac.seeds, [1, 3]);
+        assert_eq!(almanac.layers.len(), 2);
+
+        let (almanac, _) = Almanac::parse("seeds: 60\n\nmap:\n10 50 48\n100 50 1\n90 95 10");
+        assert_eq!(almanac.layers[0].get(60), 20);
+        assert_eq!(almanac.layers[0].get(96), 56);
+        assert_eq!(almanac.layers[0].get(99), 99);
+
+        let almanac = Almanac::parse_strict(crate::tests::INPUT).unwrap();
+        assert_eq!(almanac.seeds, [79, 14, 55, 13]);
+        assert_eq!(almanac.layers.len(), 7);
+    }
+}
@@ -0,0 +1,177 @@
+use std::ops::Range;
+
+/// Set of `u64` values stored as sorted, disjoint and non-adjacent ranges.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntervalSet {
+    ranges: Vec<Range<u64>>,
+}
+
+impl From<Range<u64>> for IntervalSet {
+    fn from(range: Range<u64>) -> Self {
+        Self::normalise(vec![range])
+    }
+}
+
+impl FromIterator<Range<u64>> for IntervalSet {
+    fn from_iter<T: IntoIterator<Item = Range<u64>>>(iter: T) -> Self {
+        Self::normalise(iter.into_iter().collect())
+    }
+}
+
+impl IntervalSet {
+    /// Sorts the ranges, dropping empty ones and merging overlapping or adjacent ones.
+    pub fn normalise(mut ranges: Vec<Range<u64>>) -> Self {
+        ranges.retain(|range| !range.is_empty());
+        ranges.sort_by_key(|range| range.start);
+
+        let mut merged: Vec<Range<u64>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        Self { ranges: merged }
+    }
+
+    pub fn ranges(&self) -> &[Range<u64>] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Number of values in the set.
+    pub fn len(&self) -> u64 {
+        self.ranges
+            .iter()
+            .map(|range| range.end - range.start)
+            .sum()
+    }
+
+    pub fn min(&self) -> Option<u64> {
+        self.ranges.first().map(|range| range.start)
+    }
+
+    pub fn contains(&self, value: u64) -> bool {
+        let i = self.ranges.partition_point(|range| range.end <= value);
+        self.ranges
+            .get(i)
+            .is_some_and(|range| range.contains(&value))
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        self.ranges.iter().chain(&other.ranges).cloned().collect()
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let (mut a, mut b) = (
+            self.ranges.iter().peekable(),
+            other.ranges.iter().peekable(),
+        );
+        while let (Some(x), Some(y)) = (a.peek(), b.peek()) {
+            let start = x.start.max(y.start);
+            let end = x.end.min(y.end);
+            if start < end {
+                ranges.push(start..end);
+            }
+            if x.end < y.end {
+                a.next();
+            } else {
+                b.next();
+            }
+        }
+        Self { ranges }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let mut others = other.ranges.iter().peekable();
+        for range in &self.ranges {
+            let mut cur = range.start;
+            while let Some(o) = others.peek() {
+                if o.end <= cur {
+                    others.next();
+                    continue;
+                }
+                if o.start >= range.end {
+                    break;
+                }
+                if cur < o.start {
+                    ranges.push(cur..o.start);
+                }
+                cur = o.end;
+                if o.end > range.end {
+                    break;
+                }
+                others.next();
+            }
+            if cur < range.end {
+                ranges.push(cur..range.end);
+            }
+        }
+        Self { ranges }
+    }
+
+    /// Moves every value by `offset`, panics if a value leaves the `u64` range.
+    pub fn shift(&self, offset: i128) -> Self {
+        let shift = |value: u64| u64::try_from(i128::from(value) + offset).unwrap();
+        let ranges = self
+            .ranges
+            .iter()
+            .map(|range| shift(range.start)..shift(range.end))
+            .collect();
+        Self { ranges }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IntervalSet;
+
+    #[test]
+    fn normalise() {
+        let set = IntervalSet::from_iter([8..10, 0..3, 2..5, 5..6, 7..7]);
+        assert_eq!(set.ranges(), [0..6, 8..10]);
+        assert_eq!(set.len(), 8);
+        assert!(set.contains(5));
+        assert!(!set.contains(6));
+        assert!(!set.contains(10));
+    }
+
+    #[test]
+    fn algebra() {
+        let a = IntervalSet::from_iter([0..10, 20..30]);
+        let b = IntervalSet::from_iter([5..25, 28..40]);
+
+        assert_eq!(a.union(&b), IntervalSet::from(0..40));
+        assert_eq!(a.intersection(&b).ranges(), [5..10, 20..25, 28..30]);
+        assert_eq!(a.difference(&b).ranges(), [0..5, 25..28]);
+        assert_eq!(b.difference(&a).ranges(), [10..20, 30..40]);
+        assert_eq!(a.shift(5).ranges(), [5..15, 25..35]);
+        assert_eq!(b.shift(-5).ranges(), [0..20, 23..35]);
+    }
+
+    #[test]
+    fn algebra_matches_sets() {
+        let a = IntervalSet::from_iter([1..4, 6..7, 9..15, 18..19]);
+        let b = IntervalSet::from_iter([0..2, 3..10, 12..13, 14..20]);
+
+        for x in 0..25 {
+            let (in_a, in_b) = (a.contains(x), b.contains(x));
+            assert_eq!(a.union(&b).contains(x), in_a || in_b, "union {x}");
+            assert_eq!(
+                a.intersection(&b).contains(x),
+                in_a && in_b,
+                "intersection {x}"
+            );
+            assert_eq!(
+                a.difference(&b).contains(x),
+                in_a && !in_b,
+                "difference {x}"
+            );
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use std::collections::BTreeMap;
+
+mod almanac;
+mod interval_set;
+mod range_map;
+
+pub use almanac::{validate, Almanac, Diagnostic, Issue};
+pub use interval_set::IntervalSet;
+pub use range_map::RangeMap;
+
+pub fn parse_mapping(mapping: &str) -> BTreeMap<u64, (u64, u64)> {
+    mapping
+        .split('\n')
+        .skip(1)
+        .map(|sect_line| {
+            let mut sect_line = sect_line
+                .split_whitespace()
+                .map(str::parse::<u64>)
+                .map(Result::unwrap);
+
+            let dest = sect_line.next().unwrap();
+            let source = sect_line.next().unwrap();
+            let length = sect_line.next().unwrap();
+
+            assert!(sect_line.next().is_none());
+
+            (source, (dest, length))
+        })
+        .collect::<BTreeMap<_, _>>()
+}
+
+#[cfg(test)]
+pub mod tests {
+    pub const INPUT: &str = r#"seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4"#;
+}
@@ -1,10 +1,19 @@
-use std::collections::BTreeMap;
+use day_5::{parse_mapping, validate, Almanac, Diagnostic, IntervalSet, RangeMap};
 
 const INPUT: &str = include_str!("input.txt");
 
 fn main() {
+    for diagnostic in validate(INPUT) {
+        if diagnostic.issue.is_error() {
+            eprintln!("{diagnostic}");
+        }
+    }
+
     println!("lowest location: {}", lowest_location(INPUT));
-    println!("lowest location ranged: {}", lowest_location_ranged(INPUT));
+    match lowest_location_ranged(INPUT) {
+        Ok(location) => println!("lowest location ranged: {location}"),
+        Err(_) => println!("lowest location ranged: not computed, the almanac has errors"),
+    }
 }
 
 // const CATEGORIES: &[&str] = &[
@@ -61,112 +70,25 @@ fn lowest_location(input: &str) -> u64 {
         .unwrap()
 }
 
-#[derive(PartialEq, Eq)]
-struct Range {
-    start: u64,
-    length: u64,
-}
-
-impl Ord for Range {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.start.cmp(&other.start)
-    }
-}
-
-impl PartialOrd for Range {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl Range {
-    fn new(start: u64, length: u64) -> Self {
-        Self { start, length }
-    }
-
-    fn end(&self) -> u64 {
-        self.start + self.length
-    }
-}
-
-fn lowest_location_ranged(input: &str) -> u64 {
-    let mut sections = input.split("\n\n");
-
-    let mut seed_ranges = Vec::new();
-    {
-        let seeds_section = sections.next().unwrap();
-        let (_tag, seeds) = seeds_section.split_once(':').unwrap();
-        let mut seeds = seeds
-            .split_whitespace()
-            .map(str::parse::<u64>)
-            .map(Result::unwrap);
-
-        while let (Some(start), Some(length)) = (seeds.next(), seeds.next()) {
-            seed_ranges.push(Range::new(start, length));
-        }
-    };
-
-    sections
-        .fold(seed_ranges, |prev, mapping| {
-            let mapping = parse_mapping(mapping);
-
-            prev.into_iter()
-                .flat_map(|seed| {
-                    let mut cur_seed_end = seed.end();
-                    let mut result = Vec::new();
-                    for (&map_start, (dest, length)) in mapping
-                        .range(..=seed.end())
-                        .rev()
-                        .take_while(|(&map_start, (_, length))| seed.start < map_start + length)
-                    {
-                        let shift = *dest as i64 - map_start as i64;
-                        let map_end = map_start + length;
-
-                        if map_end < cur_seed_end {
-                            Range::new(map_end, cur_seed_end - map_end);
-                        }
-
-                        let start = map_start.max(seed.start);
-                        let length = cur_seed_end.min(map_end) - map_start.max(seed.start);
-                        result.push(Range::new(start.checked_add_signed(shift).unwrap(), length));
-                        cur_seed_end = start;
-                    }
-                    if cur_seed_end > seed.start {
-                        result.push(Range::new(seed.start, cur_seed_end - seed.start));
-                    }
-                    result
-                })
-                .collect()
-        })
-        .into_iter()
-        .min()
-        .unwrap()
-        .start
-}
-
-fn parse_mapping(mapping: &str) -> BTreeMap<u64, (u64, u64)> {
-    mapping
-        .split('\n')
-        .skip(1)
-        .map(|sect_line| {
-            let mut sect_line = sect_line
-                .split_whitespace()
-                .map(str::parse::<u64>)
-                .map(Result::unwrap);
-
-            let dest = sect_line.next().unwrap();
-            let source = sect_line.next().unwrap();
-            let length = sect_line.next().unwrap();
+/// Refuses any almanac with errors, a dropped seed would pair the rest up wrongly.
+fn lowest_location_ranged(input: &str) -> Result<u64, Vec<Diagnostic>> {
+    let almanac = Almanac::parse_strict(input)?;
 
-            assert!(sect_line.next().is_none());
+    let seeds = IntervalSet::from_iter(
+        almanac
+            .seeds
+            .chunks_exact(2)
+            .map(|pair| pair[0]..pair[0] + pair[1]),
+    );
+    let seed_to_location = RangeMap::compose(&almanac.layers);
 
-            (source, (dest, length))
-        })
-        .collect::<BTreeMap<_, _>>()
+    Ok(seed_to_location.image(&seeds).min().unwrap())
 }
 
 #[cfg(test)]
 pub mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
     const INPUT: &str = r#"seeds: 79 14 55 13
 
 seed-to-soil map:
@@ -210,6 +132,48 @@ humidity-to-location map:
     #[test]
     fn lowest_location_ranged() {
         let lowest_location = super::lowest_location_ranged(INPUT);
-        assert_eq!(lowest_location, 46);
+        assert_eq!(lowest_location, Ok(46));
+
+        for seeds in ["seeds: 79 1x4 55 13", "seeds: 79 14 55"] {
+            let input = INPUT.replacen("seeds: 79 14 55 13", seeds, 1);
+            assert!(super::lowest_location_ranged(&input).is_err(), "{seeds}");
+        }
+    }
+
+    #[test]
+    fn lowest_location_ranged_matches_expanded() {
+        let mut rng = StdRng::seed_from_u64(5);
+
+        for _ in 0..200 {
+            let mut seed_ranges = Vec::new();
+            let mut seeds = Vec::new();
+            for _ in 0..rng.gen_range(1..4) {
+                let start = rng.gen_range(0..100);
+                let length = rng.gen_range(1..20);
+                seed_ranges.push(format!("{start} {length}"));
+                seeds.extend((start..start + length).map(|seed| seed.to_string()));
+            }
+
+            let mut sections = String::new();
+            for section in 0..7 {
+                sections.push_str(&format!("\n\nmap {section}:"));
+                let mut source = 0;
+                for _ in 0..rng.gen_range(0..5) {
+                    source += rng.gen_range(0..20);
+                    let length = rng.gen_range(1..20);
+                    let dest = rng.gen_range(0..120);
+                    sections.push_str(&format!("\n{dest} {source} {length}"));
+                    source += length;
+                }
+            }
+
+            let ranged = format!("seeds: {}{sections}", seed_ranges.join(" "));
+            let expanded = format!("seeds: {}{sections}", seeds.join(" "));
+            assert_eq!(
+                super::lowest_location_ranged(&ranged),
+                Ok(super::lowest_location(&expanded)),
+                "{ranged}"
+            );
+        }
     }
 }
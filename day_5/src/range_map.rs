@@ -0,0 +1,276 @@
+use std::{collections::BTreeMap, ops::Range};
+
+use crate::IntervalSet;
+
+/// Piecewise-linear map over `0..u64::MAX`.
+/// Values outside of every mapped range map to themselves.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RangeMap {
+    /// source start -> (destination start, length)
+    mappings: BTreeMap<u64, (u64, u64)>,
+}
+
+impl From<BTreeMap<u64, (u64, u64)>> for RangeMap {
+    fn from(mappings: BTreeMap<u64, (u64, u64)>) -> Self {
+        Self { mappings }
+    }
+}
+
+impl RangeMap {
+    pub fn get(&self, value: u64) -> u64 {
+        self.mappings
+            .range(..=value)
+            .next_back()
+            .and_then(|(source, (dest, length))| {
+                let distance = value - source;
+                (distance < *length).then_some(dest + distance)
+            })
+            .unwrap_or(value)
+    }
+
+    /// Splits `range` into consecutive pieces that are each shifted by a single offset,
+    /// yielding every piece with the destination of its first value.
+    pub fn pieces(&self, range: Range<u64>) -> Vec<(Range<u64>, u64)> {
+        let mut pieces = Vec::new();
+        if range.is_empty() {
+            return pieces;
+        }
+
+        let first = self
+            .mappings
+            .range(..=range.start)
+            .next_back()
+            .map_or(range.start, |(&start, _)| start);
+
+        let mut cur = range.start;
+        for (source, dest) in self.segments(first) {
+            if source.start >= range.end {
+                break;
+            }
+            if cur < source.start {
+                pieces.push((cur..source.start, cur));
+                cur = source.start;
+            }
+            let end = source.end.min(range.end);
+            if cur < end {
+                pieces.push((cur..end, dest + (cur - source.start)));
+                cur = end;
+            }
+        }
+        if cur < range.end {
+            pieces.push((cur..range.end, cur));
+        }
+        pieces
+    }
+
+    /// Map equivalent to applying `self` and then `next`.
+    pub fn then(&self, next: &RangeMap) -> RangeMap {
+        let mut pieces = Vec::new();
+        for (source, dest) in self.pieces(0..u64::MAX) {
+            let image = dest..dest + (source.end - source.start);
+            for (piece, out) in next.pieces(image) {
+                let start = source.start + (piece.start - dest);
+                pieces.push((start..start + (piece.end - piece.start), out));
+            }
+        }
+        Self::from_pieces(pieces)
+    }
+
+    /// Composes the layers in order, the first layer being applied first.
+    pub fn compose<'m>(layers: impl IntoIterator<Item = &'m RangeMap>) -> RangeMap {
+        layers
+            .into_iter()
+            .fold(RangeMap::default(), |acc, layer| acc.then(layer))
+    }
+
+    /// `None` if the map isn't a bijection.
+    pub fn inverse(&self) -> Option<RangeMap> {
+        let mut images = self
+            .pieces(0..u64::MAX)
+            .into_iter()
+            .map(|(source, dest)| (dest..dest + (source.end - source.start), source.start))
+            .collect::<Vec<_>>();
+        images.sort_by_key(|(image, _)| image.start);
+
+        let mut cur = 0;
+        for (image, _) in &images {
+            if image.start != cur {
+                return None;
+            }
+            cur = image.end;
+        }
+        (cur == u64::MAX).then(|| Self::from_pieces(images))
+    }
+
+    /// Every input value mapping into `range`.
+    pub fn preimage(&self, range: Range<u64>) -> IntervalSet {
+        self.pieces(0..u64::MAX)
+            .into_iter()
+            .filter_map(|(source, dest)| {
+                let image_end = dest + (source.end - source.start);
+                let start = range.start.max(dest);
+                let end = range.end.min(image_end);
+                (start < end).then(|| source.start + (start - dest)..source.start + (end - dest))
+            })
+            .collect()
+    }
+
+    /// Every output value of the inputs in `set`.
+    pub fn image(&self, set: &IntervalSet) -> IntervalSet {
+        let mut unmapped = set.clone();
+        let mut image = IntervalSet::default();
+        for (source, dest) in self.segments(0) {
+            let shift = i128::from(dest) - i128::from(source.start);
+            let source = IntervalSet::from(source);
+            image = image.union(&set.intersection(&source).shift(shift));
+            unmapped = unmapped.difference(&source);
+        }
+        image.union(&unmapped)
+    }
+
+    /// Lowest output over `range`, `None` if the range is empty.
+    pub fn min_over(&self, range: Range<u64>) -> Option<u64> {
+        self.pieces(range).into_iter().map(|(_, dest)| dest).min()
+    }
+
+    /// Mapped source ranges from the mapping starting at `from` on, each cut short by the
+    /// next mapping's start, so a later start wins on overlap just as in [`RangeMap::get`].
+    fn segments(&self, from: u64) -> impl Iterator<Item = (Range<u64>, u64)> + '_ {
+        let next_starts = self
+            .mappings
+            .range(from..)
+            .skip(1)
+            .map(|(&start, _)| start)
+            .chain(Some(u64::MAX));
+        self.mappings.range(from..).zip(next_starts).map(
+            |((&start, &(dest, length)), next_start)| {
+                (start..(start + length).min(next_start), dest)
+            },
+        )
+    }
+
+    fn from_pieces(pieces: impl IntoIterator<Item = (Range<u64>, u64)>) -> Self {
+        let mut mappings = BTreeMap::<u64, (u64, u64)>::new();
+        let mut last: Option<(u64, u64, u64)> = None;
+        for (source, dest) in pieces {
+            if source.is_empty() || source.start == dest {
+                continue;
+            }
+            let length = source.end - source.start;
+            match &mut last {
+                Some((start, last_dest, last_length))
+                    if *start + *last_length == source.start
+                        && *last_dest + *last_length == dest =>
+                {
+                    *last_length += length;
+                }
+                _ => {
+                    if let Some((start, dest, length)) = last {
+                        mappings.insert(start, (dest, length));
+                    }
+                    last = Some((source.start, dest, length));
+                }
+            }
+        }
+        if let Some((start, dest, length)) = last {
+            mappings.insert(start, (dest, length));
+        }
+        Self { mappings }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::RangeMap;
+    use crate::{parse_mapping, tests::INPUT, IntervalSet};
+
+    fn layers() -> Vec<RangeMap> {
+        INPUT
+            .split("\n\n")
+            .skip(1)
+            .map(|section| RangeMap::from(parse_mapping(section)))
+            .collect()
+    }
+
+    #[test]
+    fn compose() {
+        let layers = layers();
+        let composed = RangeMap::compose(&layers);
+
+        for seed in 0..200 {
+            let expected = layers.iter().fold(seed, |value, layer| layer.get(value));
+            assert_eq!(composed.get(seed), expected, "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn inverse() {
+        let composed = RangeMap::compose(&layers());
+        let inverse = composed.inverse().unwrap();
+
+        for seed in 0..200 {
+            assert_eq!(inverse.get(composed.get(seed)), seed);
+        }
+
+        let squash = RangeMap::from(BTreeMap::from([(0, (10, 5))]));
+        assert_eq!(squash.inverse(), None);
+    }
+
+    #[test]
+    fn preimage() {
+        let composed = RangeMap::compose(&layers());
+
+        let seeds = composed.preimage(0..50);
+        for seed in 0..200 {
+            assert_eq!(seeds.contains(seed), composed.get(seed) < 50, "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn image() {
+        let composed = RangeMap::compose(&layers());
+
+        let seeds = IntervalSet::from_iter([79..93, 55..68]);
+        let locations = composed.image(&seeds);
+        for seed in 0..200 {
+            let location = composed.get(seed);
+            if seeds.contains(seed) {
+                assert!(locations.contains(location), "seed {seed}");
+            }
+        }
+        assert_eq!(locations.len(), seeds.len());
+        assert_eq!(locations.min(), Some(46));
+
+        // shifts wider than i64 in both directions
+        let far = RangeMap::from(BTreeMap::from([
+            (0, (u64::MAX - 10, 10)),
+            (u64::MAX - 10, (0, 10)),
+        ]));
+        let image = far.image(&IntervalSet::from_iter([2..5, u64::MAX - 8..u64::MAX - 6]));
+        assert_eq!(image.ranges(), [2..4, u64::MAX - 8..u64::MAX - 5]);
+    }
+
+    #[test]
+    fn overlapping() {
+        // the later start wins, and the earlier range does not resume after it ends
+        let map = RangeMap::from(BTreeMap::from([(0, (100, 10)), (5, (200, 2))]));
+        let composed = RangeMap::compose([&map]);
+        for value in 0..20 {
+            let expected = map.get(value);
+            assert_eq!(map.pieces(value..value + 1), [(value..value + 1, expected)]);
+            assert_eq!(map.min_over(value..value + 1), Some(expected));
+            assert_eq!(composed.get(value), expected, "value {value}");
+        }
+        assert_eq!(map.get(8), 8);
+        assert_eq!(map.pieces(0..20), [(0..5, 100), (5..7, 200), (7..20, 7)]);
+    }
+
+    #[test]
+    fn min_over() {
+        let composed = RangeMap::compose(&layers());
+        assert_eq!(composed.min_over(79..93), Some(46));
+        assert_eq!(composed.min_over(10..10), None);
+    }
+}
@@ -1,17 +1,32 @@
+use std::ops::RangeInclusive;
+
 const INPUT: &str = include_str!("input.txt");
 
 fn main() {
+    let model = RaceModel::default();
+
     println!(
         "Product of winning strategy count: {}",
-        winning_start_count(INPUT)
+        winning_start_count(INPUT, &model)
     );
     //1108800
 
-    println!("Single race: {}", single_race(INPUT));
+    println!("Single race: {}", single_race(INPUT, &model));
     // 36919753
+
+    let model = RaceModel {
+        acceleration: 2,
+        max_speed: Some(60),
+        start_delay: 2,
+        ..RaceModel::default()
+    };
+    println!(
+        "Product of winning strategy count, capped at 60: {}",
+        winning_start_count(INPUT, &model)
+    );
 }
 
-fn winning_start_count(input: &str) -> u64 {
+fn winning_start_count(input: &str, model: &RaceModel) -> u64 {
     let mut lines = input.lines().map(|line| {
         let (_tag, values) = line.split_once(':').unwrap();
         values
@@ -23,43 +38,255 @@ fn winning_start_count(input: &str) -> u64 {
         .next()
         .unwrap()
         .zip(lines.next().unwrap())
-        .map(|(time, best_distance)| winning_strategy_count(time, best_distance))
+        .map(|(time, best_distance)| winning_strategy_count(model, time, best_distance))
         .product()
 }
 
-fn single_race(input: &str) -> u64 {
+fn single_race(input: &str, model: &RaceModel) -> u64 {
     let mut lines = input.lines().map(|line| {
         let (_tag, values) = line.split_once(':').unwrap();
         values.replace(' ', "").parse::<u64>().unwrap()
     });
     let (time, best_distance) = (lines.next().unwrap(), lines.next().unwrap());
 
-    winning_strategy_count(time, best_distance)
+    winning_strategy_count(model, time, best_distance)
+}
+
+fn winning_strategy_count(model: &RaceModel, time: u64, best_distance: u64) -> u64 {
+    model
+        .winning_holds(time, best_distance)
+        .map_or(0, |holds| holds.end() - holds.start() + 1)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RaceModel {
+    /// speed gained per ms held
+    acceleration: u64,
+    max_speed: Option<u64>,
+    /// speed lost per ms travelled, the boat stops at 0
+    drag: u64,
+    /// ms between releasing the button and the boat moving
+    start_delay: u64,
+}
+
+impl Default for RaceModel {
+    fn default() -> Self {
+        Self {
+            acceleration: 1,
+            max_speed: None,
+            drag: 0,
+            start_delay: 0,
+        }
+    }
+}
+
+impl RaceModel {
+    fn speed(&self, hold: u64) -> u64 {
+        let speed = self.acceleration.saturating_mul(hold);
+        self.max_speed
+            .map_or(speed, |max_speed| speed.min(max_speed))
+    }
+
+    fn distance(&self, time: u64, hold: u64) -> u128 {
+        let travel = time.saturating_sub(self.start_delay).saturating_sub(hold) as u128;
+        let speed = self.speed(hold) as u128;
+        if self.drag == 0 {
+            return speed * travel;
+        }
+        let drag = self.drag as u128;
+        // moving ms before the boat stops
+        let moving = travel.min(speed.div_ceil(drag));
+        moving * speed - drag * moving * moving.saturating_sub(1) / 2
+    }
+
+    fn winning_holds(&self, time: u64, best_distance: u64) -> Option<RangeInclusive<u64>> {
+        let race_time = time.checked_sub(self.start_delay)?;
+        if self.acceleration == 0 || self.max_speed == Some(0) {
+            return None;
+        }
+        if self.drag > 0 {
+            return self.search_winning_holds(time, best_distance);
+        }
+
+        // a h (t - h) > b  <=>  h (t - h) > b / a, rounded down as the left side is whole
+        let accelerating = winning_holds(race_time, best_distance / self.acceleration);
+        let Some(max_speed) = self.max_speed else {
+            return accelerating;
+        };
+
+        // from `capped` on, the distance is `max_speed * (t - h)`, only shrinking with `h`
+        let capped = max_speed.div_ceil(self.acceleration);
+        let accelerating = accelerating.filter(|holds| *holds.start() < capped);
+        let cruising = race_time
+            .checked_sub(best_distance / max_speed + 1)
+            .filter(|&last| capped <= last)
+            .map(|last| capped..=last);
+
+        match (accelerating, cruising) {
+            (Some(accelerating), Some(cruising)) => Some(*accelerating.start()..=*cruising.end()),
+            (Some(accelerating), None) => {
+                Some(*accelerating.start()..=*accelerating.end().min(&(capped - 1)))
+            }
+            (None, cruising) => cruising,
+        }
+    }
+
+    /// Assumes the distance rises up to a peak hold and never rises again after it.
+    fn search_winning_holds(&self, time: u64, best_distance: u64) -> Option<RangeInclusive<u64>> {
+        let race_time = time - self.start_delay;
+        let distance = |hold| self.distance(time, hold);
+        let beats = |hold| distance(hold) > best_distance as u128;
+
+        let peak = partition_point(0, race_time, |hold| distance(hold + 1) > distance(hold));
+        if !beats(peak) {
+            return None;
+        }
+        let first = partition_point(0, peak, |hold| !beats(hold));
+        let last = partition_point(peak, race_time, beats) - 1;
+        Some(first..=last)
+    }
 }
 
-fn winning_strategy_count(time: u64, best_distance: u64) -> u64 {
-    // t + sqrt(t^2 - 4 b) - ceil(t - sqrt(t^2 - 4 b)) + 1
-    let b = f64::sqrt((time.pow(2) - 4 * (best_distance + 1)) as f64);
-    let x_0 = (time as f64 - b) / 2.0;
-    let x_1 = (time as f64 + b) / 2.0;
-    let result = x_1 - x_0.ceil() + 1.0;
-    result as _
+/// First value in `start..end` for which `pred` is false, `end` if there's none.
+fn partition_point(mut start: u64, mut end: u64, pred: impl Fn(u64) -> bool) -> u64 {
+    while start < end {
+        let mid = start + (end - start) / 2;
+        if pred(mid) {
+            start = mid + 1;
+        } else {
+            end = mid;
+        }
+    }
+    start
+}
+
+/// Hold times `h` with `h * (time - h) > best_distance`, `None` if the record can't be beaten.
+fn winning_holds(time: u64, best_distance: u64) -> Option<RangeInclusive<u64>> {
+    let beats = |hold: u64| hold as u128 * (time - hold) as u128 > best_distance as u128;
+
+    // h^2 - t h + b < 0  <=>  (t - sqrt(t^2 - 4 b)) / 2 < h < (t + sqrt(t^2 - 4 b)) / 2
+    let discriminant = (time as u128 * time as u128).checked_sub(4 * best_distance as u128)?;
+    let root = discriminant.isqrt() as u64;
+
+    // `root` is floored, so the first winning hold is at most one off in either direction
+    let mut first = (time - root) / 2;
+    while first > 0 && beats(first - 1) {
+        first -= 1;
+    }
+    while first <= time / 2 && !beats(first) {
+        first += 1;
+    }
+
+    (first <= time / 2).then(|| first..=time - first)
 }
 
 #[cfg(test)]
 mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::RaceModel;
+
     const INPUT: &str = r#"Time:      7  15   30
 Distance:  9  40  200"#;
 
     #[test]
     fn product_of_winning_strategy_count() {
-        let result = super::winning_start_count(INPUT);
+        let result = super::winning_start_count(INPUT, &RaceModel::default());
         assert_eq!(result, 288)
     }
 
     #[test]
     fn single_race() {
-        let result = super::single_race(INPUT);
+        let result = super::single_race(INPUT, &RaceModel::default());
         assert_eq!(result, 71503)
     }
+
+    #[test]
+    fn winning_holds() {
+        assert_eq!(super::winning_holds(7, 9), Some(2..=5));
+        assert_eq!(super::winning_holds(30, 200), Some(11..=19));
+        // tangent: holding 5 exactly ties the record
+        assert_eq!(super::winning_holds(10, 25), None);
+        assert_eq!(super::winning_holds(10, 24), Some(5..=5));
+        assert_eq!(super::winning_holds(0, 0), None);
+        assert_eq!(super::winning_holds(1, 0), None);
+        assert_eq!(super::winning_holds(2, 0), Some(1..=1));
+
+        let time = u64::MAX;
+        let best_distance = u64::MAX - 1;
+        let holds = super::winning_holds(time, best_distance).unwrap();
+        let distance = |hold: u64| hold as u128 * (time - hold) as u128;
+        assert!(distance(*holds.start()) > best_distance as u128);
+        assert!(distance(holds.start() - 1) <= best_distance as u128);
+        assert_eq!(*holds.end(), time - holds.start());
+    }
+
+    #[test]
+    fn winning_holds_match_brute_force() {
+        let mut rng = StdRng::seed_from_u64(6);
+
+        for _ in 0..2000 {
+            let time = rng.gen_range(0..500u64);
+            let best_distance = rng.gen_range(0..=time * time / 4 + 2);
+
+            let winning = (0..=time)
+                .filter(|hold| hold * (time - hold) > best_distance)
+                .collect::<Vec<_>>();
+            let expected = winning
+                .first()
+                .zip(winning.last())
+                .map(|(&first, &last)| first..=last);
+
+            assert_eq!(
+                super::winning_holds(time, best_distance),
+                expected,
+                "time {time}, distance {best_distance}"
+            );
+        }
+    }
+
+    #[test]
+    fn race_models_match_brute_force() {
+        let mut rng = StdRng::seed_from_u64(30);
+
+        for _ in 0..2000 {
+            let model = RaceModel {
+                acceleration: rng.gen_range(0..4),
+                max_speed: rng.gen_bool(0.5).then(|| rng.gen_range(0..60)),
+                drag: if rng.gen_bool(0.5) {
+                    rng.gen_range(1..4)
+                } else {
+                    0
+                },
+                start_delay: rng.gen_range(0..5),
+            };
+            let time = rng.gen_range(0..100u64);
+            let peak = (0..=time)
+                .map(|hold| model.distance(time, hold))
+                .max()
+                .unwrap();
+            let best_distance = rng.gen_range(0..=peak as u64 + 1);
+
+            let winning = (0..=time)
+                .filter(|&hold| model.distance(time, hold) > best_distance as u128)
+                .collect::<Vec<_>>();
+            let expected = winning
+                .first()
+                .zip(winning.last())
+                .map(|(&first, &last)| first..=last);
+            assert_eq!(
+                winning.len() as u64,
+                expected
+                    .clone()
+                    .map_or(0, |holds| holds.end() - holds.start() + 1),
+                "{model:?} winning holds aren't contiguous"
+            );
+
+            assert_eq!(
+                model.winning_holds(time, best_distance),
+                expected,
+                "{model:?}, time {time}, distance {best_distance}"
+            );
+        }
+    }
 }
@@ -0,0 +1,226 @@
+use std::cmp::Ordering;
+
+use rand::Rng;
+
+use crate::{Card, Hand, HandType, Rules};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    pub hand: String,
+    pub ty: HandType,
+    /// the hand with every wildcard replaced by the card it stood for
+    pub substitution: String,
+    /// 1 for the weakest hand
+    pub rank: usize,
+    pub winnings: usize,
+}
+
+/// One explanation per input line, in input order.
+pub fn explain_hands(input: &str, rules: &Rules) -> Vec<Explanation> {
+    let mut hands = input
+        .lines()
+        .enumerate()
+        .map(|(line, hand_bid)| {
+            let (hand, bid) = hand_bid.split_once(' ').unwrap();
+            (
+                Hand::parse(hand, rules),
+                bid.parse::<usize>().unwrap(),
+                line,
+            )
+        })
+        .collect::<Vec<_>>();
+    hands.sort();
+
+    let mut explanations = hands
+        .into_iter()
+        .enumerate()
+        .map(|(i, (hand, bid, line))| {
+            let explanation = Explanation {
+                hand: rules.format(&hand.cards),
+                ty: hand.ty,
+                substitution: rules.format(&best_substitution(&hand, rules)),
+                rank: i + 1,
+                winnings: bid * (i + 1),
+            };
+            (line, explanation)
+        })
+        .collect::<Vec<_>>();
+    explanations.sort_by_key(|(line, _)| *line);
+    explanations
+        .into_iter()
+        .map(|(_, explanation)| explanation)
+        .collect()
+}
+
+/// Strongest hand reachable by replacing the wildcards with other cards.
+fn best_substitution(hand: &Hand, rules: &Rules) -> Vec<Card> {
+    let wild = (0..hand.cards.len())
+        .filter(|&i| rules.is_wild(&hand.cards[i]))
+        .collect::<Vec<_>>();
+    if wild.is_empty() {
+        return hand.cards.clone();
+    }
+
+    let natural_rules = Rules {
+        wildcards: "",
+        ..rules.clone()
+    };
+    let ranks = (0..rules.order.len())
+        .filter(|&rank| !rules.is_wild(&Card { rank, suit: None }))
+        .collect::<Vec<_>>();
+    // a flush needs the wildcards to take the suit of the natural cards
+    let flush_suit = hand
+        .cards
+        .iter()
+        .find(|card| !rules.is_wild(card))
+        .and_then(|card| card.suit);
+
+    let mut best: Option<(Hand, Vec<Card>)> = None;
+    // wildcards are interchangeable, so only non-decreasing picks are tried
+    let mut picks = vec![0; wild.len()];
+    loop {
+        let mut cards = hand.cards.clone();
+        for (&i, &pick) in wild.iter().zip(&picks) {
+            cards[i] = Card {
+                rank: ranks[pick],
+                suit: cards[i].suit.and(flush_suit.or(cards[i].suit)),
+            };
+        }
+        let substituted = Hand::new(cards.clone(), &natural_rules);
+        if best.as_ref().is_none_or(|(best, _)| substituted > *best) {
+            best = Some((substituted, cards));
+        }
+
+        let Some(i) = (0..picks.len()).rev().find(|&i| picks[i] + 1 < ranks.len()) else {
+            break;
+        };
+        picks[i] += 1;
+        let pick = picks[i];
+        picks[i..].fill(pick);
+    }
+    best.unwrap().1
+}
+
+/// Every card drawn uniformly from `rules.order`, with a random suit if `suited`.
+pub fn random_hand(rules: &Rules, suited: bool, rng: &mut impl Rng) -> Hand {
+    let cards = (0..rules.hand_size)
+        .map(|_| Card {
+            rank: rng.gen_range(0..rules.order.len()),
+            suit: suited.then(|| ['c', 'd', 'h', 's'][rng.gen_range(0..4)]),
+        })
+        .collect();
+    Hand::new(cards, rules)
+}
+
+/// Share of the pot `hand` is expected to win against `opponents` random hands,
+/// a tie with `n` opponents being worth `1 / (n + 1)`.
+pub fn equity(
+    hand: &Hand,
+    rules: &Rules,
+    opponents: usize,
+    trials: usize,
+    rng: &mut impl Rng,
+) -> f64 {
+    let suited = hand.cards.iter().all(|card| card.suit.is_some());
+    // suits never break a tie
+    let key = (hand.strength, &hand.tiebreak);
+
+    let won = (0..trials)
+        .map(|_| {
+            let mut ties = 0;
+            for _ in 0..opponents {
+                let opponent = random_hand(rules, suited, rng);
+                match (opponent.strength, &opponent.tiebreak).cmp(&key) {
+                    Ordering::Greater => return 0.0,
+                    Ordering::Equal => ties += 1,
+                    Ordering::Less => (),
+                }
+            }
+            1.0 / (ties + 1) as f64
+        })
+        .sum::<f64>();
+    won / trials as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::{equity, explain_hands, Explanation};
+    use crate::{Hand, HandType, Rules};
+
+    const INPUT: &str = r#"32T3K 765
+T55J5 684
+KK677 28
+KTJJT 220
+QQQJA 483"#;
+
+    #[test]
+    fn explain_jokers() {
+        let explanations = explain_hands(INPUT, &Rules::jokers());
+        let explanation = |hand: &str, ty, substitution: &str, rank, winnings| Explanation {
+            hand: hand.to_string(),
+            ty,
+            substitution: substitution.to_string(),
+            rank,
+            winnings,
+        };
+        assert_eq!(
+            explanations,
+            [
+                explanation("32T3K", HandType::OnePair, "32T3K", 1, 765),
+                explanation("T55J5", HandType::FourOAK, "T5555", 3, 3 * 684),
+                explanation("KK677", HandType::TwoPair, "KK677", 2, 2 * 28),
+                explanation("KTJJT", HandType::FourOAK, "KTTTT", 5, 5 * 220),
+                explanation("QQQJA", HandType::FourOAK, "QQQQA", 4, 4 * 483),
+            ]
+        );
+        let total = explanations.iter().map(|e| e.winnings).sum::<usize>();
+        assert_eq!(total, 5905);
+    }
+
+    #[test]
+    fn explain_poker_wildcards() {
+        let rules = Rules {
+            order: "J23456789TQKA",
+            wildcards: "J",
+            ..Rules::poker()
+        };
+        let explanations = explain_hands("2h3hJs5h6h 1\nJcJdJsJhJc 1", &rules);
+        assert_eq!(explanations[0].ty, HandType::StraightFlush);
+        assert_eq!(explanations[0].substitution, "2h3h4h5h6h");
+        assert_eq!(explanations[1].ty, HandType::FiveOAK);
+        assert_eq!(explanations[1].substitution, "AcAdAsAhAc");
+    }
+
+    #[test]
+    fn equity_bounds() {
+        let mut rng = StdRng::seed_from_u64(7);
+
+        for rules in [Rules::standard(), Rules::jokers()] {
+            let strong = Hand::parse("AAAAA", &rules);
+            let weak = Hand::parse("23457", &rules);
+            let middle = Hand::parse("KK677", &rules);
+
+            let strong = equity(&strong, &rules, 3, 2000, &mut rng);
+            let weak = equity(&weak, &rules, 3, 2000, &mut rng);
+            let middle = equity(&middle, &rules, 3, 2000, &mut rng);
+            assert!(strong > 0.99, "{strong}");
+            assert!(weak < 0.01, "{weak}");
+            assert!(weak < middle && middle < strong, "{middle}");
+        }
+    }
+
+    #[test]
+    fn equity_ignores_suits() {
+        // every opponent holds the same ranks in other suits, so every trial is a tie
+        let rules = Rules {
+            order: "A",
+            ..Rules::standard()
+        };
+        let hand = Hand::parse("AcAcAcAcAc", &rules);
+        let mut rng = StdRng::seed_from_u64(33);
+        assert_eq!(equity(&hand, &rules, 1, 200, &mut rng), 0.5);
+        assert_eq!(equity(&hand, &rules, 3, 200, &mut rng), 0.25);
+    }
+}
@@ -0,0 +1,319 @@
+mod analysis;
+
+pub use analysis::{equity, explain_hands, random_hand, Explanation};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HandType {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOAK,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOAK,
+    StraightFlush,
+    FiveOAK,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Pattern {
+    /// groups of at least these sizes
+    Counts(Vec<usize>),
+    /// every card a rank apart in `Rules::order`
+    Straight,
+    /// every card of the same suit
+    Flush,
+    StraightFlush,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tiebreak {
+    /// card by card, as dealt
+    InOrder,
+    /// biggest group first, higher rank first within same-sized groups
+    Grouped,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    /// cards from weakest to strongest
+    pub order: &'static str,
+    /// cards counted as whichever card makes the best hand
+    pub wildcards: &'static str,
+    pub hand_size: usize,
+    /// weakest to strongest, a hand is of the strongest type whose pattern it matches
+    pub hand_types: Vec<(HandType, Pattern)>,
+    pub tiebreak: Tiebreak,
+}
+
+impl Rules {
+    pub fn standard() -> Self {
+        Self {
+            order: "23456789TJQKA",
+            wildcards: "",
+            hand_size: 5,
+            hand_types: vec![
+                (HandType::HighCard, Pattern::Counts(vec![1])),
+                (HandType::OnePair, Pattern::Counts(vec![2])),
+                (HandType::TwoPair, Pattern::Counts(vec![2, 2])),
+                (HandType::ThreeOAK, Pattern::Counts(vec![3])),
+                (HandType::FullHouse, Pattern::Counts(vec![3, 2])),
+                (HandType::FourOAK, Pattern::Counts(vec![4])),
+                (HandType::FiveOAK, Pattern::Counts(vec![5])),
+            ],
+            tiebreak: Tiebreak::InOrder,
+        }
+    }
+
+    pub fn jokers() -> Self {
+        Self {
+            order: "J23456789TQKA",
+            wildcards: "J",
+            ..Self::standard()
+        }
+    }
+
+    pub fn jokers_and_deuces() -> Self {
+        Self {
+            order: "J23456789TQKA",
+            wildcards: "J2",
+            ..Self::standard()
+        }
+    }
+
+    pub fn poker() -> Self {
+        Self {
+            hand_types: vec![
+                (HandType::HighCard, Pattern::Counts(vec![1])),
+                (HandType::OnePair, Pattern::Counts(vec![2])),
+                (HandType::TwoPair, Pattern::Counts(vec![2, 2])),
+                (HandType::ThreeOAK, Pattern::Counts(vec![3])),
+                (HandType::Straight, Pattern::Straight),
+                (HandType::Flush, Pattern::Flush),
+                (HandType::FullHouse, Pattern::Counts(vec![3, 2])),
+                (HandType::FourOAK, Pattern::Counts(vec![4])),
+                (HandType::StraightFlush, Pattern::StraightFlush),
+                (HandType::FiveOAK, Pattern::Counts(vec![5])),
+            ],
+            tiebreak: Tiebreak::Grouped,
+            ..Self::standard()
+        }
+    }
+
+    fn rank(&self, card: char) -> usize {
+        self.order
+            .find(card)
+            .unwrap_or_else(|| panic!("unknown card {card}"))
+    }
+
+    pub fn format(&self, cards: &[Card]) -> String {
+        cards
+            .iter()
+            .flat_map(|card| [Some(self.order.as_bytes()[card.rank] as char), card.suit])
+            .flatten()
+            .collect()
+    }
+
+    fn is_wild(&self, card: &Card) -> bool {
+        self.wildcards
+            .contains(self.order.as_bytes()[card.rank] as char)
+    }
+
+    /// Index into `hand_types` of the strongest type the hand matches.
+    fn strength(&self, cards: &[Card]) -> usize {
+        let (wild, natural): (Vec<&Card>, Vec<&Card>) =
+            cards.iter().partition(|card| self.is_wild(card));
+
+        let mut card_count = vec![0usize; self.order.len()];
+        for card in &natural {
+            card_count[card.rank] += 1;
+        }
+        card_count.sort_unstable_by(|a, b| b.cmp(a));
+        card_count[0] += wild.len();
+
+        let straight = || {
+            let mut ranks = natural.iter().map(|card| card.rank).collect::<Vec<_>>();
+            ranks.sort_unstable();
+            ranks.dedup();
+            // wildcards fill the gaps, but the run has to fit in `order`
+            ranks.len() == natural.len()
+                && cards.len() <= self.order.len()
+                && ranks
+                    .first()
+                    .zip(ranks.last())
+                    .is_none_or(|(low, high)| high - low < cards.len())
+        };
+        let flush = || {
+            if cards.iter().any(|card| card.suit.is_none()) {
+                return false;
+            }
+            let mut suits = natural.iter().map(|card| card.suit);
+            let suit = suits.next().flatten();
+            suits.all(|other| other == suit)
+        };
+
+        self.hand_types
+            .iter()
+            .rposition(|(_, pattern)| match pattern {
+                Pattern::Counts(counts) => {
+                    counts.len() <= card_count.len()
+                        && counts
+                            .iter()
+                            .zip(&card_count)
+                            .all(|(need, count)| count >= need)
+                }
+                Pattern::Straight => straight(),
+                Pattern::Flush => flush(),
+                Pattern::StraightFlush => straight() && flush(),
+            })
+            .unwrap_or_else(|| panic!("no hand type for {cards:?}"))
+    }
+
+    fn tiebreak(&self, cards: &[Card]) -> Vec<usize> {
+        match self.tiebreak {
+            Tiebreak::InOrder => cards.iter().map(|card| card.rank).collect(),
+            Tiebreak::Grouped => {
+                let mut card_count = vec![0usize; self.order.len()];
+                for card in cards {
+                    card_count[card.rank] += 1;
+                }
+                let mut ranks = cards.iter().map(|card| card.rank).collect::<Vec<_>>();
+                ranks.sort_unstable_by_key(|&rank| std::cmp::Reverse((card_count[rank], rank)));
+                ranks
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Card {
+    /// index in `Rules::order`
+    pub rank: usize,
+    pub suit: Option<char>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Hand {
+    strength: usize,
+    tiebreak: Vec<usize>,
+    pub cards: Vec<Card>,
+    pub ty: HandType,
+}
+
+impl Hand {
+    /// Cards may be followed by a suit, `c`, `d`, `h` or `s`.
+    pub fn parse(hand: &str, rules: &Rules) -> Self {
+        let mut cards = Vec::with_capacity(rules.hand_size);
+        let mut chars = hand.chars().peekable();
+        while let Some(c) = chars.next() {
+            let suit = chars.next_if(|s| matches!(s, 'c' | 'd' | 'h' | 's'));
+            cards.push(Card {
+                rank: rules.rank(c),
+                suit,
+            });
+        }
+        assert_eq!(
+            cards.len(),
+            rules.hand_size,
+            "hand {hand:?} has the wrong size"
+        );
+        Self::new(cards, rules)
+    }
+
+    pub fn new(cards: Vec<Card>, rules: &Rules) -> Self {
+        let strength = rules.strength(&cards);
+        Self {
+            strength,
+            tiebreak: rules.tiebreak(&cards),
+            cards,
+            ty: rules.hand_types[strength].0,
+        }
+    }
+}
+
+/// Hands with their bids, weakest first.
+pub fn rank_hands(input: &str, rules: &Rules) -> Vec<(Hand, usize)> {
+    let mut hands = input
+        .lines()
+        .map(|line| {
+            let (hand, bid) = line.split_once(' ').unwrap();
+            let bid = bid.parse::<usize>().unwrap();
+            (Hand::parse(hand, rules), bid)
+        })
+        .collect::<Vec<_>>();
+    hands.sort();
+    hands
+}
+
+pub fn total_winnings(input: &str, rules: &Rules) -> usize {
+    rank_hands(input, rules)
+        .into_iter()
+        .enumerate()
+        .map(|(i, (_, bid))| bid * (i + 1))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rank_hands, Hand, HandType, Rules};
+
+    #[test]
+    fn poker_hand_types() {
+        let rules = Rules::poker();
+        let cases = [
+            ("2h3h4h5h6h", HandType::StraightFlush),
+            ("2h3d4h5h6h", HandType::Straight),
+            ("2h9h4h5hKh", HandType::Flush),
+            ("KhKdKs2h2d", HandType::FullHouse),
+            ("TJQKA", HandType::Straight),
+            ("2h9h4h5hK", HandType::HighCard),
+            ("34562", HandType::Straight),
+            ("3456A", HandType::HighCard),
+        ];
+        for (hand, expected) in cases {
+            assert_eq!(Hand::parse(hand, &rules).ty, expected, "{hand}");
+        }
+    }
+
+    #[test]
+    fn poker_wildcards() {
+        let rules = Rules {
+            order: "J23456789TQKA",
+            wildcards: "J",
+            ..Rules::poker()
+        };
+        let cases = [
+            ("2h3hJs5h6h", HandType::StraightFlush),
+            ("2h3hJsJd6h", HandType::StraightFlush),
+            ("9hThJsQhKh", HandType::StraightFlush),
+            ("2h3h4hJs2d", HandType::ThreeOAK),
+            ("2h9hJs5hKh", HandType::Flush),
+            ("JhJdJsJcJh", HandType::FiveOAK),
+        ];
+        for (hand, expected) in cases {
+            assert_eq!(Hand::parse(hand, &rules).ty, expected, "{hand}");
+        }
+    }
+
+    #[test]
+    fn precedence_and_tiebreak() {
+        let input =
+            "KhKdKs2h2d 1\n2h9h4h5hKh 10\n2h2d2sKhKd 100\n9h9dAsKh2c 1000\n9s9cKdAh3c 10000";
+
+        let ranked = rank_hands(input, &Rules::poker())
+            .into_iter()
+            .map(|(_, bid)| bid)
+            .collect::<Vec<_>>();
+        assert_eq!(ranked, [1000, 10000, 10, 100, 1]);
+
+        let mut rules = Rules::poker();
+        rules.hand_types.swap(5, 6);
+        rules.tiebreak = super::Tiebreak::InOrder;
+        let ranked = rank_hands(input, &rules)
+            .into_iter()
+            .map(|(_, bid)| bid)
+            .collect::<Vec<_>>();
+        assert_eq!(ranked, [10000, 1000, 100, 1, 10]);
+    }
+}
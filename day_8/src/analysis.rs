@@ -0,0 +1,283 @@
+use std::fmt::{Display, Write};
+
+use crate::Network;
+
+const COLORS: &[&str] = &[
+    "lightblue",
+    "lightcoral",
+    "palegreen",
+    "khaki",
+    "plum",
+    "lightsalmon",
+    "paleturquoise",
+    "thistle",
+    "wheat",
+    "lightpink",
+];
+
+/// Strongly connected components, numbered in reverse topological order:
+/// edges only lead to components with the same or a lower number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Components {
+    pub ids: Vec<usize>,
+    pub count: usize,
+}
+
+impl<'s> Network<'s> {
+    /// Nodes reachable from `start` by any choice of left and right.
+    pub fn reachable_from(&self, start: usize) -> Vec<bool> {
+        let mut reachable = vec![false; self.len()];
+        reachable[start] = true;
+        let mut stack = vec![start];
+        while let Some(node) = stack.pop() {
+            for next in self.neighbours(node) {
+                if !reachable[next] {
+                    reachable[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+        reachable
+    }
+
+    /// Tarjan's algorithm, with an explicit call stack so long chains can't overflow it.
+    pub fn components(&self) -> Components {
+        let mut index = vec![None; self.len()];
+        let mut low_link = vec![0; self.len()];
+        let mut on_stack = vec![false; self.len()];
+        let mut stack = Vec::new();
+        let mut next_index = 0;
+        let mut ids = vec![0; self.len()];
+        let mut count = 0;
+
+        for root in 0..self.len() {
+            if index[root].is_some() {
+                continue;
+            }
+            // (node, neighbours visited so far)
+            let mut calls = vec![(root, 0)];
+            while let Some(&(node, edge)) = calls.last() {
+                if index[node].is_none() {
+                    index[node] = Some(next_index);
+                    low_link[node] = next_index;
+                    next_index += 1;
+                    stack.push(node);
+                    on_stack[node] = true;
+                }
+
+                if let Some(&next) = self.neighbours(node).get(edge) {
+                    calls.last_mut().unwrap().1 += 1;
+                    match index[next] {
+                        None => calls.push((next, 0)),
+                        Some(next_index) if on_stack[next] => {
+                            low_link[node] = low_link[node].min(next_index);
+                        }
+                        Some(_) => (),
+                    }
+                    continue;
+                }
+
+                calls.pop();
+                if let Some(&(parent, _)) = calls.last() {
+                    low_link[parent] = low_link[parent].min(low_link[node]);
+                }
+                if Some(low_link[node]) == index[node] {
+                    while let Some(member) = stack.pop() {
+                        on_stack[member] = false;
+                        ids[member] = count;
+                        if member == node {
+                            break;
+                        }
+                    }
+                    count += 1;
+                }
+            }
+        }
+        Components { ids, count }
+    }
+
+    /// Nodes from which no node matching `is_end` can be reached.
+    pub fn dead_ends(&self, is_end: impl Fn(usize) -> bool) -> Vec<usize> {
+        let mut incoming = vec![Vec::new(); self.len()];
+        for node in 0..self.len() {
+            for next in self.neighbours(node) {
+                incoming[next].push(node);
+            }
+        }
+
+        let mut live = (0..self.len()).map(&is_end).collect::<Vec<_>>();
+        let mut stack = (0..self.len())
+            .filter(|&node| live[node])
+            .collect::<Vec<_>>();
+        while let Some(node) = stack.pop() {
+            for &prev in &incoming[node] {
+                if !live[prev] {
+                    live[prev] = true;
+                    stack.push(prev);
+                }
+            }
+        }
+        (0..self.len()).filter(|&node| !live[node]).collect()
+    }
+
+    /// Graphviz source with nodes filled by strongly connected component.
+    pub fn to_dot(&self) -> String {
+        let components = self.components();
+
+        let mut dot = String::from("digraph network {\n");
+        for node in 0..self.len() {
+            let name = self.name(node);
+            let shape = match () {
+                _ if name.ends_with('A') => "doublecircle",
+                _ if name.ends_with('Z') => "doubleoctagon",
+                _ => "ellipse",
+            };
+            let color = COLORS[components.ids[node] % COLORS.len()];
+            writeln!(
+                dot,
+                "    {name:?} [shape={shape}, style=filled, fillcolor={color}];"
+            )
+            .unwrap();
+        }
+        for node in 0..self.len() {
+            let [left, right] = self.neighbours(node);
+            let name = self.name(node);
+            writeln!(dot, "    {name:?} -> {:?} [label=L];", self.name(left)).unwrap();
+            writeln!(dot, "    {name:?} -> {:?} [label=R];", self.name(right)).unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Summary for the `AAA` to `ZZZ` walk and the ghost walks from `..A` to `..Z`.
+    pub fn report(&self) -> Report<'s> {
+        let unreachable = match self.id("AAA") {
+            Some(start) => {
+                let reachable = self.reachable_from(start);
+                (0..self.len())
+                    .filter(|&node| !reachable[node])
+                    .map(|node| self.name(node))
+                    .collect()
+            }
+            None => Vec::new(),
+        };
+        let end_reachable = self
+            .id("AAA")
+            .zip(self.id("ZZZ"))
+            .is_some_and(|(start, end)| self.reachable_from(start)[end]);
+
+        let components = self.components();
+        let start_components = (0..self.len())
+            .filter(|&node| self.name(node).ends_with('A'))
+            .map(|node| (self.name(node), components.ids[node]))
+            .collect();
+        let dead_ends = self
+            .dead_ends(|node| self.name(node).ends_with('Z'))
+            .into_iter()
+            .map(|node| self.name(node))
+            .collect();
+
+        Report {
+            unreachable,
+            end_reachable,
+            components: components.count,
+            start_components,
+            dead_ends,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report<'s> {
+    /// nodes `AAA` can never get to
+    pub unreachable: Vec<&'s str>,
+    /// whether any path leads from `AAA` to `ZZZ`
+    pub end_reachable: bool,
+    pub components: usize,
+    /// component of every `..A` node
+    pub start_components: Vec<(&'s str, usize)>,
+    /// nodes with no path to any `..Z` node
+    pub dead_ends: Vec<&'s str>,
+}
+
+impl Display for Report<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "unreachable from AAA: {}", self.unreachable.len())?;
+        writeln!(f, "ZZZ reachable from AAA: {}", self.end_reachable)?;
+        writeln!(f, "strongly connected components: {}", self.components)?;
+        for (start, component) in &self.start_components {
+            writeln!(f, "{start} is in component {component}")?;
+        }
+        write!(f, "dead ends: {}", self.dead_ends.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Network;
+
+    const INPUT: &str = r#"LR
+
+AAA = (BBB, XXX)
+BBB = (AAA, CCC)
+CCC = (ZZZ, CCC)
+ZZZ = (ZZZ, ZZZ)
+XXX = (XXX, XXX)
+QQQ = (AAA, ZZZ)
+11A = (XXX, XXX)"#;
+
+    #[test]
+    fn components() {
+        let network = Network::compile(INPUT);
+        let components = network.components();
+        let component = |name| components.ids[network.id(name).unwrap()];
+
+        assert_eq!(components.count, 6);
+        assert_eq!(component("AAA"), component("BBB"));
+        assert_ne!(component("AAA"), component("CCC"));
+        // edges only lead to lower components
+        for node in 0..network.len() {
+            for next in network.neighbours(node) {
+                assert!(components.ids[next] <= components.ids[node]);
+            }
+        }
+    }
+
+    #[test]
+    fn long_chain() {
+        let len = 100_000;
+        let mut input = String::from("L\n\n");
+        for i in 0..len {
+            let next = (i + 1).min(len - 1);
+            input.push_str(&format!("N{i:06} = (N{next:06}, N{next:06})\n"));
+        }
+        let network = Network::compile(&input);
+        let components = network.components();
+        assert_eq!(components.count, len);
+        assert_eq!(components.ids[0], len - 1);
+    }
+
+    #[test]
+    fn report() {
+        let network = Network::compile(INPUT);
+        let report = network.report();
+
+        assert_eq!(report.unreachable, ["11A", "QQQ"]);
+        assert!(report.end_reachable);
+        assert_eq!(report.dead_ends, ["11A", "XXX"]);
+        assert_eq!(report.start_components.len(), 2);
+
+        let input = INPUT.replace("CCC = (ZZZ", "CCC = (CCC");
+        let network = Network::compile(&input);
+        assert!(!network.report().end_reachable);
+    }
+
+    #[test]
+    fn to_dot() {
+        let dot = Network::compile(INPUT).to_dot();
+        assert!(dot.starts_with("digraph network {\n"));
+        assert!(dot.contains("\"AAA\" [shape=doublecircle"));
+        assert!(dot.contains("\"CCC\" -> \"ZZZ\" [label=L];"));
+        assert_eq!(dot.lines().count(), 2 + 7 + 14);
+    }
+}
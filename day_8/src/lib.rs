@@ -0,0 +1,349 @@
+use std::{collections::HashMap, fmt::Display};
+
+use num::Integer;
+
+mod analysis;
+mod network;
+
+pub use analysis::{Components, Report};
+pub use network::{HitTable, Network};
+
+pub fn parse(input: &str) -> (&str, HashMap<&str, (&str, &str)>) {
+    let (inst, network) = input.split_once("\n\n").unwrap();
+
+    let network = network
+        .lines()
+        .map(|line| {
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("{line:?} does not have a '='"))
+                .unwrap();
+            let (left, right) = value
+                .trim()
+                .trim_start_matches('(')
+                .trim_end_matches(')')
+                .split_once(',')
+                .unwrap();
+            (key.trim(), (left.trim(), right.trim()))
+        })
+        .collect::<HashMap<_, _>>();
+
+    (inst.trim(), network)
+}
+
+/// Steps after which a walker stands on an end node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hits {
+    /// hits before the walker enters its cycle
+    pub prefix: Vec<usize>,
+    /// first step of the cycle of (node, instruction index) states
+    pub cycle_start: usize,
+    pub cycle_len: usize,
+    /// hits during the first pass through the cycle, repeating every `cycle_len` steps
+    pub cycle: Vec<usize>,
+}
+
+impl Hits {
+    pub fn trace(
+        network: &HashMap<&str, (&str, &str)>,
+        inst: &str,
+        start: &str,
+        is_end: impl Fn(&str) -> bool,
+    ) -> Self {
+        let inst = inst.as_bytes();
+        let mut seen = HashMap::new();
+        let mut hits = Vec::new();
+
+        let mut cur_node = start;
+        let mut step = 0;
+        let cycle_start = loop {
+            let i = step % inst.len();
+            if let Some(&first_step) = seen.get(&(cur_node, i)) {
+                break first_step;
+            }
+            seen.insert((cur_node, i), step);
+            if is_end(cur_node) {
+                hits.push(step);
+            }
+
+            let (left, right) = network[cur_node];
+            cur_node = match inst[i] {
+                b'L' => left,
+                b'R' => right,
+                inst => panic!("invalid instruction {:?}", inst as char),
+            };
+            step += 1;
+        };
+
+        let cycle = hits.split_off(hits.partition_point(|&hit| hit < cycle_start));
+        Self {
+            prefix: hits,
+            cycle_start,
+            cycle_len: step - cycle_start,
+            cycle,
+        }
+    }
+
+    pub fn contains(&self, step: usize) -> bool {
+        if step < self.cycle_start {
+            self.prefix.binary_search(&step).is_ok()
+        } else {
+            let step = self.cycle_start + (step - self.cycle_start) % self.cycle_len;
+            self.cycle.binary_search(&step).is_ok()
+        }
+    }
+}
+
+/// Why [`first_sync`] has no step to give.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncError {
+    /// the walkers are never all on an end node at once
+    NoSolution,
+    /// the combined cycle does not fit in an `i128`
+    Overflow,
+    /// more than [`MAX_RESIDUES`] combinations of cycle hits are still possible
+    TooManyResidues,
+}
+
+impl Display for SyncError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SyncError::NoSolution => write!(f, "the walkers are never in sync"),
+            SyncError::Overflow => write!(f, "the combined cycle overflows an i128"),
+            SyncError::TooManyResidues => {
+                write!(f, "more than {MAX_RESIDUES} combinations of cycle hits")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SyncError {}
+
+/// Most residue classes [`first_sync`] tracks at once.
+pub const MAX_RESIDUES: usize = 1 << 20;
+
+/// First step at which every walker stands on an end node.
+///
+/// Every combination of cycle hits is a residue class, so with coprime cycle lengths the
+/// classes multiply with each walker's hit count. Walkers with the fewest hits are folded
+/// in first, and the search gives up once more than [`MAX_RESIDUES`] classes remain.
+pub fn first_sync(walkers: &[Hits]) -> Result<u128, SyncError> {
+    let settled = walkers
+        .iter()
+        .map(|hits| hits.cycle_start)
+        .max()
+        .ok_or(SyncError::NoSolution)?;
+    if let Some(step) = (0..settled).find(|&step| walkers.iter().all(|hits| hits.contains(step))) {
+        return Ok(step as u128);
+    }
+
+    let mut walkers = walkers.iter().collect::<Vec<_>>();
+    walkers.sort_by_key(|hits| hits.cycle.len());
+
+    // from `settled` on every walker is in its cycle, so each hit is a residue class
+    let mut residues = vec![(0u128, 1u128)];
+    for hits in walkers {
+        let modulus = hits.cycle_len as u128;
+        let mut next = Vec::new();
+        for &acc in &residues {
+            for &hit in &hits.cycle {
+                if let Some(residue) = crt(acc, (hit as u128 % modulus, modulus))? {
+                    next.push(residue);
+                }
+            }
+            if next.len() > MAX_RESIDUES {
+                return Err(SyncError::TooManyResidues);
+            }
+        }
+        next.sort_unstable();
+        next.dedup();
+        residues = next;
+    }
+
+    let settled = settled as u128;
+    residues
+        .into_iter()
+        .map(|(residue, modulus)| {
+            if residue >= settled {
+                Ok(residue)
+            } else {
+                (settled - residue)
+                    .div_ceil(modulus)
+                    .checked_mul(modulus)
+                    .and_then(|step| step.checked_add(residue))
+                    .ok_or(SyncError::Overflow)
+            }
+        })
+        .min_by_key(|step| *step.as_ref().unwrap_or(&u128::MAX))
+        .unwrap_or(Err(SyncError::NoSolution))
+}
+
+/// Generalised Chinese remainder theorem, for moduli that need not be coprime.
+/// `None` if the classes are incompatible.
+fn crt((a, n): (u128, u128), (b, m): (u128, u128)) -> Result<Option<(u128, u128)>, SyncError> {
+    let [a, n, b, m] =
+        [a, n, b, m].map(|value| i128::try_from(value).map_err(|_| SyncError::Overflow));
+    let (a, n, b, m) = (a?, n?, b?, m?);
+    let gcd = n.extended_gcd(&m);
+    let diff = b - a;
+    if diff % gcd.gcd != 0 {
+        return Ok(None);
+    }
+
+    let lcm = (n / gcd.gcd).checked_mul(m).ok_or(SyncError::Overflow)?;
+    // n k = diff (mod m)
+    let step = m / gcd.gcd;
+    let k = mul_mod(
+        (diff / gcd.gcd).rem_euclid(step) as u128,
+        gcd.x.rem_euclid(step) as u128,
+        step as u128,
+    ) as i128;
+    // k < m / gcd, so n k < lcm
+    let x = (n * k)
+        .checked_add(a)
+        .ok_or(SyncError::Overflow)?
+        .rem_euclid(lcm);
+    Ok(Some((x as u128, lcm as u128)))
+}
+
+/// `a b mod m` by doubling, for `a, b < m <= i128::MAX` so no sum overflows.
+fn mul_mod(mut a: u128, mut b: u128, m: u128) -> u128 {
+    let mut product = 0;
+    while b > 0 {
+        if b & 1 == 1 {
+            product = (product + a) % m;
+        }
+        a = (a + a) % m;
+        b >>= 1;
+    }
+    product
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{crt, first_sync, parse, Hits, SyncError, MAX_RESIDUES};
+
+    #[test]
+    fn crt_non_coprime() {
+        assert_eq!(crt((2, 4), (4, 6)), Ok(Some((10, 12))));
+        assert_eq!(crt((1, 4), (2, 6)), Ok(None));
+        assert_eq!(crt((0, 1), (3, 5)), Ok(Some((3, 5))));
+
+        let big = 1 << 100;
+        assert_eq!(crt((1, big), (0, big + 1)), Err(SyncError::Overflow));
+        assert_eq!(crt((0, u128::MAX), (0, 2)), Err(SyncError::Overflow));
+        assert_eq!(
+            crt((3, big), (3 + big, big * 2)),
+            Ok(Some((3 + big, big * 2)))
+        );
+        // the inverse times the difference overflows, the combined modulus does not
+        assert_eq!(
+            crt((1, 3), (0, big + 1)),
+            Ok(Some((2 * (big + 1), 3 * (big + 1))))
+        );
+    }
+
+    /// Steps all walkers together, each with its own instruction index.
+    fn brute_force(input: &str, limit: usize) -> Option<u128> {
+        let (inst, network) = parse(input);
+        let mut nodes = network
+            .keys()
+            .filter(|node| node.ends_with('A'))
+            .copied()
+            .collect::<Vec<_>>();
+        for (step, inst) in inst.chars().cycle().take(limit).enumerate() {
+            if nodes.iter().all(|node| node.ends_with('Z')) {
+                return Some(step as u128);
+            }
+            for node in &mut nodes {
+                let (left, right) = network[*node];
+                *node = if inst == 'L' { left } else { right };
+            }
+        }
+        None
+    }
+
+    fn solve(input: &str) -> Result<u128, SyncError> {
+        let (inst, network) = parse(input);
+        let walkers = network
+            .keys()
+            .filter(|node| node.ends_with('A'))
+            .map(|start| Hits::trace(&network, inst, start, |node| node.ends_with('Z')))
+            .collect::<Vec<_>>();
+        first_sync(&walkers)
+    }
+
+    #[test]
+    fn offsets_and_prefix_hits() {
+        // 11A hits a Z at step 1 and then every 3 steps from step 4, 22A every 4 steps from step 2
+        const INPUT: &str = r#"L
+
+11A = (11Z, XXX)
+11Z = (11B, XXX)
+11B = (11C, XXX)
+11C = (13Z, XXX)
+13Z = (11D, XXX)
+11D = (11E, XXX)
+11E = (13Z, XXX)
+22A = (22B, XXX)
+22B = (22Z, XXX)
+22Z = (22C, XXX)
+22C = (22D, XXX)
+22D = (22E, XXX)
+22E = (22Z, XXX)
+XXX = (XXX, XXX)"#;
+        assert_eq!(solve(INPUT).ok(), brute_force(INPUT, 1000));
+        assert_eq!(solve(INPUT), Ok(10));
+
+        let input = INPUT.replace("22A = (22B", "22A = (22Z");
+        assert_eq!(solve(&input).ok(), brute_force(&input, 1000));
+        assert_eq!(solve(&input), Ok(1));
+    }
+
+    #[test]
+    fn never_in_sync() {
+        const INPUT: &str = r#"L
+
+11A = (11Z, XXX)
+11Z = (11A, XXX)
+22A = (22B, XXX)
+22B = (22Z, XXX)
+22Z = (22C, XXX)
+22C = (22Z, XXX)
+XXX = (XXX, XXX)"#;
+        assert_eq!(brute_force(INPUT, 1000), None);
+        assert_eq!(solve(INPUT), Err(SyncError::NoSolution));
+    }
+
+    #[test]
+    fn multi_hit_walkers() {
+        let walker = |cycle_start: usize, cycle_len: usize, cycle: &[usize]| Hits {
+            prefix: Vec::new(),
+            cycle_start,
+            cycle_len,
+            cycle: cycle.to_vec(),
+        };
+        let walkers = [
+            walker(3, 6, &[5, 7]),
+            walker(0, 10, &[1, 4, 5, 9]),
+            walker(2, 15, &[4, 7, 11, 13]),
+        ];
+        let expected = (0..1000).find(|&step| walkers.iter().all(|hits| hits.contains(step)));
+        assert_eq!(expected, Some(11));
+        assert_eq!(first_sync(&walkers), Ok(11));
+
+        let walkers = [walker(0, 4, &[1, 3]), walker(0, 6, &[0, 2, 4])];
+        assert_eq!(first_sync(&walkers), Err(SyncError::NoSolution));
+        assert_eq!(first_sync(&[]), Err(SyncError::NoSolution));
+
+        // twenty hits on each of five coprime cycles: 20^5 classes
+        let walkers = [101, 103, 107, 109, 113]
+            .map(|len| walker(0, len, &(0..20).map(|hit| hit * 5).collect::<Vec<_>>()));
+        assert!(20usize.pow(5) > MAX_RESIDUES);
+        assert_eq!(first_sync(&walkers), Err(SyncError::TooManyResidues));
+
+        // coprime cycles whose product exceeds i128
+        let walkers = [(1 << 62) - 1, 1 << 62, (1 << 62) + 1].map(|len| walker(0, len, &[1, 2]));
+        assert_eq!(first_sync(&walkers), Err(SyncError::Overflow));
+    }
+}
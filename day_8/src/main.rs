@@ -1,92 +1,41 @@
-use std::collections::HashMap;
-
-use num::Integer;
+use day_8::{first_sync, parse, Hits, Network, SyncError};
 
 const INPUT: &str = include_str!("input.txt");
 
 fn main() {
-    println!("Step Count: {}", step_count(INPUT));
-    println!("Ghost Step Count: {}", multi_path(INPUT));
-}
+    match step_count(INPUT) {
+        Some(step_count) => println!("Step Count: {step_count}"),
+        None => println!("ZZZ is never reached from AAA"),
+    }
 
-fn step_count(input: &str) -> usize {
-    let (inst, network) = input.split_once("\n\n").unwrap();
-
-    let network = network
-        .lines()
-        .map(|line| {
-            let (key, value) = line
-                .split_once('=')
-                .ok_or_else(|| format!("{line:?} does not have a '='"))
-                .unwrap();
-            let (left, right) = value
-                .trim()
-                .trim_start_matches('(')
-                .trim_end_matches(')')
-                .split_once(',')
-                .unwrap();
-            (key.trim(), (left.trim(), right.trim()))
-        })
-        .collect::<HashMap<_, _>>();
-
-    let mut cur_node = "AAA";
-    let mut step_count = 0;
-    let mut insts = inst.chars().cycle();
-    while cur_node != "ZZZ" {
-        let inst = insts.next().unwrap();
-        let (left, right) = network[cur_node];
-        cur_node = match inst {
-            'L' => left,
-            'R' => right,
-            inst => panic!("invalid instruction {inst:?}"),
-        };
-        step_count += 1;
+    let network = Network::compile(INPUT);
+    let end = network.position_after(network.id("AAA").unwrap(), 10u64.pow(15));
+    println!("After 10^15 steps: {}", network.name(end));
+    match multi_path(INPUT) {
+        Ok(step_count) => println!("Ghost Step Count: {step_count}"),
+        Err(SyncError::NoSolution) => println!("Ghosts never all reach a Z node together"),
+        Err(err) => println!("Ghost Step Count: not computed, {err}"),
     }
-    step_count
 }
 
-fn multi_path(input: &str) -> usize {
-    let (inst, network) = input.split_once("\n\n").unwrap();
-
-    let network = network
-        .lines()
-        .map(|line| {
-            let (key, value) = line
-                .split_once('=')
-                .ok_or_else(|| format!("{line:?} does not have a '='"))
-                .unwrap();
-            let (left, right) = value
-                .trim()
-                .trim_start_matches('(')
-                .trim_end_matches(')')
-                .split_once(',')
-                .unwrap();
-            (key.trim(), (left.trim(), right.trim()))
-        })
-        .collect::<HashMap<_, _>>();
-
-    let mut insts = inst.chars().cycle();
+fn step_count(input: &str) -> Option<u64> {
+    let network = Network::compile(input);
+    let zzz = network.id("ZZZ")?;
 
     network
+        .hits(|node| node == zzz)
+        .first_hit(network.id("AAA")?)
+}
+
+fn multi_path(input: &str) -> Result<u128, SyncError> {
+    let (inst, network) = parse(input);
+
+    let ghosts = network
         .keys()
         .filter(|node| node.ends_with('A'))
-        .copied()
-        .map(|mut cur_node| {
-            let mut step_count = 0;
-            while !cur_node.ends_with('Z') {
-                let inst = insts.next().unwrap();
-                let (left, right) = network[cur_node];
-                cur_node = match inst {
-                    'L' => left,
-                    'R' => right,
-                    inst => panic!("invalid instruction {inst:?}"),
-                };
-                step_count += 1;
-            }
-            step_count
-        })
-        .reduce(|acc, cur| acc.lcm(&cur))
-        .unwrap()
+        .map(|start| Hits::trace(&network, inst, start, |node| node.ends_with('Z')))
+        .collect::<Vec<_>>();
+    first_sync(&ghosts)
 }
 
 #[cfg(test)]
@@ -103,7 +52,7 @@ EEE = (EEE, EEE)
 GGG = (GGG, GGG)
 ZZZ = (ZZZ, ZZZ)"#;
         let step_count = super::step_count(INPUT);
-        assert_eq!(step_count, 2)
+        assert_eq!(step_count, Some(2))
     }
 
     #[test]
@@ -114,7 +63,18 @@ AAA = (BBB, BBB)
 BBB = (AAA, ZZZ)
 ZZZ = (ZZZ, ZZZ)"#;
         let step_count = super::step_count(INPUT);
-        assert_eq!(step_count, 6)
+        assert_eq!(step_count, Some(6))
+    }
+
+    #[test]
+    fn navigate_unreachable() {
+        const INPUT: &str = r#"LLR
+
+AAA = (BBB, BBB)
+BBB = (AAA, AAA)
+ZZZ = (ZZZ, ZZZ)"#;
+        let step_count = super::step_count(INPUT);
+        assert_eq!(step_count, None)
     }
 
     #[test]
@@ -131,6 +91,6 @@ ZZZ = (ZZZ, ZZZ)"#;
 XXX = (XXX, XXX)"#;
 
         let step_count = super::multi_path(INPUT);
-        assert_eq!(step_count, 6)
+        assert_eq!(step_count, Ok(6))
     }
 }
@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+
+use crate::parse;
+
+/// Desert map with nodes interned to dense ids, walked with binary lifting
+/// over whole passes through the instructions.
+#[derive(Debug, Clone)]
+pub struct Network<'s> {
+    names: Vec<&'s str>,
+    ids: HashMap<&'s str, usize>,
+    /// `[left, right]` per node
+    edges: Vec<[usize; 2]>,
+    /// 0 for left, 1 for right
+    inst: Vec<usize>,
+    /// `jumps[k][node]` is where a walker starting at `node` is after `2^k` passes
+    jumps: Vec<Vec<usize>>,
+}
+
+impl<'s> Network<'s> {
+    pub fn compile(input: &'s str) -> Self {
+        let (inst, network) = parse(input);
+
+        let mut names = network.keys().copied().collect::<Vec<_>>();
+        names.sort_unstable();
+        let ids = names
+            .iter()
+            .enumerate()
+            .map(|(id, name)| (*name, id))
+            .collect::<HashMap<_, _>>();
+        let edges = names
+            .iter()
+            .map(|name| {
+                let (left, right) = network[name];
+                [ids[left], ids[right]]
+            })
+            .collect();
+        let inst = inst
+            .chars()
+            .map(|inst| match inst {
+                'L' => 0,
+                'R' => 1,
+                inst => panic!("invalid instruction {inst:?}"),
+            })
+            .collect();
+
+        let mut network = Self {
+            names,
+            ids,
+            edges,
+            inst,
+            jumps: Vec::new(),
+        };
+        let pass = (0..network.len())
+            .map(|node| network.walk(node, 0, network.inst.len()))
+            .collect::<Vec<_>>();
+        network.jumps = Self::lift(pass);
+        network
+    }
+
+    /// Doubles `base` until it covers `2^63` applications.
+    fn lift(base: Vec<usize>) -> Vec<Vec<usize>> {
+        let mut jumps = vec![base];
+        for k in 1..u64::BITS as usize {
+            let prev = &jumps[k - 1];
+            let next = prev.iter().map(|&node| prev[node]).collect();
+            jumps.push(next);
+        }
+        jumps
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn id(&self, name: &str) -> Option<usize> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: usize) -> &'s str {
+        self.names[id]
+    }
+
+    pub fn neighbours(&self, node: usize) -> [usize; 2] {
+        self.edges[node]
+    }
+
+    /// Instructions in one pass.
+    pub fn period(&self) -> usize {
+        self.inst.len()
+    }
+
+    /// Follows `steps` instructions from `node`, starting at instruction `first_inst`.
+    fn walk(&self, mut node: usize, first_inst: usize, steps: usize) -> usize {
+        for i in first_inst..first_inst + steps {
+            node = self.edges[node][self.inst[i % self.inst.len()]];
+        }
+        node
+    }
+
+    /// Where a walker starting at `start` is after `steps` steps.
+    pub fn position_after(&self, start: usize, steps: u64) -> usize {
+        let period = self.period() as u64;
+        let (passes, rest) = (steps / period, steps % period);
+
+        let mut node = start;
+        for (k, jumps) in self.jumps.iter().enumerate() {
+            if passes >> k & 1 == 1 {
+                node = jumps[node];
+            }
+        }
+        self.walk(node, 0, rest as usize)
+    }
+
+    /// Precomputes where `pred` holds for [`HitTable::first_hit`].
+    pub fn hits(&self, pred: impl Fn(usize) -> bool) -> HitTable<'_, 's> {
+        let first = (0..self.len())
+            .map(|start| {
+                let mut node = start;
+                (0..self.period()).find(|&i| {
+                    let hit = pred(node);
+                    node = self.edges[node][self.inst[i]];
+                    hit
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut any = vec![first.iter().map(Option::is_some).collect::<Vec<_>>()];
+        for k in 1..self.jumps.len() {
+            let prev = &any[k - 1];
+            let next = (0..self.len())
+                .map(|node| prev[node] || prev[self.jumps[k - 1][node]])
+                .collect();
+            any.push(next);
+        }
+
+        HitTable {
+            network: self,
+            first,
+            any,
+        }
+    }
+}
+
+/// Nodes matching a predicate, ready for first hit queries.
+pub struct HitTable<'n, 's> {
+    network: &'n Network<'s>,
+    /// step of the first hit within one pass starting at each node
+    first: Vec<Option<usize>>,
+    /// `any[k][node]`: a walker starting at `node` hits within `2^k` passes
+    any: Vec<Vec<bool>>,
+}
+
+impl HitTable<'_, '_> {
+    /// First step at which a walker from `start` stands on a matching node,
+    /// `None` if it never does.
+    pub fn first_hit(&self, start: usize) -> Option<u64> {
+        let mut node = start;
+        let mut passes = 0u64;
+        for k in (0..self.any.len()).rev() {
+            if !self.any[k][node] {
+                node = self.network.jumps[k][node];
+                passes += 1 << k;
+            }
+        }
+        let step = self.first[node]?;
+        Some(passes * self.network.period() as u64 + step as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Network;
+
+    const INPUT: &str = r#"LLR
+
+AAA = (BBB, BBB)
+BBB = (AAA, ZZZ)
+ZZZ = (ZZZ, ZZZ)"#;
+
+    const CYCLE: &str = r#"LL
+
+AAA = (BBB, XXX)
+BBB = (CCC, XXX)
+CCC = (DDD, XXX)
+DDD = (EEE, XXX)
+EEE = (AAA, XXX)
+XXX = (XXX, XXX)"#;
+
+    #[test]
+    fn position_after() {
+        for input in [INPUT, CYCLE] {
+            let network = Network::compile(input);
+            let start = network.id("AAA").unwrap();
+            let mut node = start;
+            for steps in 0..50 {
+                assert_eq!(network.position_after(start, steps), node, "{steps}");
+                node = network.walk(node, steps as usize, 1);
+            }
+        }
+
+        // AAA is back at AAA every 5 steps
+        let network = Network::compile(CYCLE);
+        let start = network.id("AAA").unwrap();
+        let node = network.position_after(start, 10u64.pow(15) + 3);
+        assert_eq!(network.name(node), "DDD");
+    }
+
+    #[test]
+    fn first_hit() {
+        let network = Network::compile(INPUT);
+        let zzz = network.id("ZZZ").unwrap();
+        let hits = network.hits(|node| node == zzz);
+        assert_eq!(hits.first_hit(network.id("AAA").unwrap()), Some(6));
+        assert_eq!(hits.first_hit(zzz), Some(0));
+
+        let network = Network::compile(CYCLE);
+        let eee = network.id("EEE").unwrap();
+        let hits = network.hits(|node| node == eee);
+        assert_eq!(hits.first_hit(network.id("AAA").unwrap()), Some(4));
+        assert_eq!(hits.first_hit(network.id("XXX").unwrap()), None);
+        let hits = network.hits(|node| network.name(node) == "QQQ");
+        assert_eq!(hits.first_hit(network.id("AAA").unwrap()), None);
+    }
+}
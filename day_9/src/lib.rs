@@ -0,0 +1,170 @@
+use std::fmt::Display;
+
+use num::{BigInt, One, Zero};
+
+mod polynomial;
+
+pub use polynomial::Polynomial;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// every row of the difference table has a non-zero value
+    NoZeroRow,
+    NotANumber(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::NoZeroRow => write!(f, "the differences never reach an all-zero row"),
+            Error::NotANumber(field) => write!(f, "{field:?} is not a number"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// OASIS readings kept in Newton forward-difference form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct History {
+    /// first value of every difference row before the all-zero one
+    leading: Vec<BigInt>,
+    len: usize,
+}
+
+impl History {
+    pub fn new(values: &[i64]) -> Result<Self, Error> {
+        let mut row = values.iter().copied().map(BigInt::from).collect::<Vec<_>>();
+        let mut leading = Vec::new();
+        while !row.iter().all(Zero::is_zero) {
+            if row.len() == 1 {
+                return Err(Error::NoZeroRow);
+            }
+            leading.push(row[0].clone());
+            row = row
+                .windows(2)
+                .map(|pair| {
+                    let [x, y] = pair else { panic!() };
+                    y - x
+                })
+                .collect();
+        }
+        if row.is_empty() {
+            return Err(Error::NoZeroRow);
+        }
+
+        Ok(Self {
+            leading,
+            len: values.len(),
+        })
+    }
+
+    pub fn parse(line: &str) -> Result<Self, Error> {
+        let values = line
+            .split_whitespace()
+            .map(|field| {
+                field
+                    .parse::<i64>()
+                    .map_err(|_| Error::NotANumber(field.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::new(&values)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Degree of the polynomial through the readings, `None` for an all-zero history.
+    pub fn degree(&self) -> Option<usize> {
+        self.leading.len().checked_sub(1)
+    }
+
+    /// Value at `index`, 0 being the first reading. Negative indices extrapolate backwards.
+    pub fn value_at(&self, index: &BigInt) -> BigInt {
+        // y(x) = sum_j binomial(x, j) * delta^j y(0)
+        let mut binomial = BigInt::one();
+        let mut value = BigInt::zero();
+        for (j, delta) in self.leading.iter().enumerate() {
+            if j > 0 {
+                binomial = binomial * (index - (j - 1)) / j;
+            }
+            value += &binomial * delta;
+        }
+        value
+    }
+
+    /// Interpolating polynomial in the reading index, exact rational coefficients.
+    pub fn polynomial(&self) -> Polynomial {
+        Polynomial::from_differences(&self.leading)
+    }
+
+    /// Value `steps` readings after the last one.
+    pub fn forward(&self, steps: u64) -> BigInt {
+        self.value_at(&(BigInt::from(self.len) - 1 + steps))
+    }
+
+    /// Value `steps` readings before the first one.
+    pub fn backward(&self, steps: u64) -> BigInt {
+        self.value_at(&-BigInt::from(steps))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::BigInt;
+
+    use super::{Error, History};
+
+    #[test]
+    fn degree() {
+        let history = History::parse("0 3 6 9 12 15").unwrap();
+        assert_eq!(history.degree(), Some(1));
+        let history = History::parse("10 13 16 21 30 45").unwrap();
+        assert_eq!(history.degree(), Some(3));
+        let history = History::parse("7 7 7").unwrap();
+        assert_eq!(history.degree(), Some(0));
+        let history = History::parse("0 0").unwrap();
+        assert_eq!(history.degree(), None);
+        assert_eq!(history.degree(), history.polynomial().degree());
+    }
+
+    #[test]
+    fn no_zero_row() {
+        assert_eq!(History::parse("1 2 4 8 16"), Err(Error::NoZeroRow));
+        assert_eq!(History::parse("5"), Err(Error::NoZeroRow));
+        assert_eq!(History::parse(""), Err(Error::NoZeroRow));
+        assert_eq!(
+            History::parse("1 x 3"),
+            Err(Error::NotANumber("x".to_string()))
+        );
+    }
+
+    #[test]
+    fn far_extrapolation() {
+        // n^2
+        let history = History::parse("0 1 4 9 16").unwrap();
+        assert_eq!(history.forward(1), BigInt::from(25));
+        assert_eq!(history.backward(3), BigInt::from(9));
+
+        let steps = 10u64.pow(18);
+        assert_eq!(history.forward(steps), BigInt::from(steps + 4).pow(2));
+        let index = BigInt::from(10u128.pow(30));
+        assert_eq!(history.value_at(&index), index.pow(2));
+    }
+
+    #[test]
+    fn matches_stepwise_extrapolation() {
+        let history = History::parse("10 13 16 21 30 45").unwrap();
+        let mut values = vec![10i64, 13, 16, 21, 30, 45];
+        for steps in 1..20 {
+            let next = History::new(&values).unwrap().forward(1);
+            assert_eq!(history.forward(steps), next);
+            values.push(next.try_into().unwrap());
+        }
+    }
+}
@@ -1,70 +1,61 @@
+use day_9::History;
+use num::BigInt;
+
 const INPUT: &str = include_str!("input.txt");
 
 fn main() {
-    println!("Sum of predictions: {}", predict(INPUT));
-    println!("Back: {}", extrapolate_back(INPUT));
+    println!("Sum of predictions: {}", predict(INPUT, 1));
+    println!("Back: {}", extrapolate_back(INPUT, 1));
+    let max_degree = histories(INPUT)
+        .filter_map(|history| history.degree())
+        .max();
+    println!("Highest degree: {}", max_degree.unwrap_or_default());
+    if let Some(history) = histories(INPUT).next() {
+        println!("First history: y = {}", history.polynomial());
+    }
 }
 
-fn predict(input: &str) -> i32 {
-    piramids(input)
-        .map(|rows| {
-            rows.into_iter()
-                .rev()
-                .fold(0, |acc, cur| cur.last().unwrap() + acc)
-        })
-        .sum()
+fn predict(input: &str, steps: u64) -> BigInt {
+    histories(input).map(|history| history.forward(steps)).sum()
 }
-fn extrapolate_back(input: &str) -> i32 {
-    piramids(input)
-        .map(|rows| {
-            rows.into_iter()
-                .rev()
-                .fold(0, |acc, cur| cur.first().unwrap() - acc)
-        })
+
+fn extrapolate_back(input: &str, steps: u64) -> BigInt {
+    histories(input)
+        .map(|history| history.backward(steps))
         .sum()
 }
 
-fn piramids(input: &str) -> impl Iterator<Item = Vec<Vec<i32>>> + '_ {
-    input.lines().map(|line| {
-        let mut rows = Vec::new();
-        rows.push(
-            line.split_whitespace()
-                .map(str::parse::<i32>)
-                .collect::<Result<Vec<_>, _>>()
-                .unwrap(),
-        );
-
-        while !rows.last().unwrap().iter().all(|val| *val == 0) {
-            rows.push(
-                rows.last()
-                    .unwrap()
-                    .windows(2)
-                    .map(|pair| {
-                        let [x, y] = pair else { panic!() };
-                        y - x
-                    })
-                    .collect(),
-            )
-        }
-        rows
-    })
+fn histories(input: &str) -> impl Iterator<Item = History> + '_ {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| History::parse(line).unwrap_or_else(|err| panic!("history {i}: {err}")))
 }
 
 #[cfg(test)]
 mod tests {
+    use num::BigInt;
+
     const INPUT: &str = r#"0 3 6 9 12 15
 1 3 6 10 15 21
 10 13 16 21 30 45"#;
 
     #[test]
     fn predict() {
-        let result = super::predict(INPUT);
-        assert_eq!(result, 114);
+        let result = super::predict(INPUT, 1);
+        assert_eq!(result, BigInt::from(114));
     }
 
     #[test]
     fn extrapolate_back() {
-        let result = super::extrapolate_back(INPUT);
-        assert_eq!(result, 2);
+        let result = super::extrapolate_back(INPUT, 1);
+        assert_eq!(result, BigInt::from(2));
+    }
+
+    #[test]
+    fn predict_further() {
+        // 18, 28 and 68 are followed by 21, 36 and 101
+        let result = super::predict(INPUT, 2);
+        assert_eq!(result, BigInt::from(21 + 36 + 101));
     }
 }
@@ -0,0 +1,142 @@
+use std::fmt::Display;
+
+use num::{BigInt, BigRational, One, Signed, Zero};
+
+/// Polynomial with exact rational coefficients, lowest power first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polynomial {
+    coefficients: Vec<BigRational>,
+}
+
+impl Polynomial {
+    /// Trailing zero coefficients are dropped.
+    pub fn new(mut coefficients: Vec<BigRational>) -> Self {
+        while coefficients.last().is_some_and(Zero::is_zero) {
+            coefficients.pop();
+        }
+        Self { coefficients }
+    }
+
+    /// Newton form `sum_j delta^j / j! * x (x - 1) ... (x - j + 1)`.
+    pub fn from_differences(leading: &[BigInt]) -> Self {
+        let mut coefficients = vec![BigRational::zero(); leading.len()];
+        // x (x - 1) ... (x - j + 1), lowest power first
+        let mut falling = vec![BigInt::one()];
+        let mut factorial = BigInt::one();
+        for (j, delta) in leading.iter().enumerate() {
+            if j > 0 {
+                factorial *= j;
+                let mut next = vec![BigInt::zero(); falling.len() + 1];
+                for (power, coefficient) in falling.iter().enumerate() {
+                    next[power + 1] += coefficient;
+                    next[power] -= coefficient * (j - 1);
+                }
+                falling = next;
+            }
+            for (power, coefficient) in falling.iter().enumerate() {
+                coefficients[power] += BigRational::new(coefficient * delta, factorial.clone());
+            }
+        }
+        Self::new(coefficients)
+    }
+
+    pub fn coefficients(&self) -> &[BigRational] {
+        &self.coefficients
+    }
+
+    /// `None` for the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        self.coefficients.len().checked_sub(1)
+    }
+
+    pub fn eval(&self, x: &BigRational) -> BigRational {
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(BigRational::zero(), |acc, coefficient| {
+                acc * x + coefficient
+            })
+    }
+}
+
+impl Display for Polynomial {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut terms = self
+            .coefficients
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, coefficient)| !coefficient.is_zero())
+            .peekable();
+        if terms.peek().is_none() {
+            return write!(f, "0");
+        }
+
+        let mut first = true;
+        for (power, coefficient) in terms {
+            match (first, coefficient.is_negative()) {
+                (true, true) => write!(f, "-")?,
+                (true, false) => (),
+                (false, true) => write!(f, " - ")?,
+                (false, false) => write!(f, " + ")?,
+            }
+            first = false;
+
+            let magnitude = coefficient.abs();
+            if !magnitude.is_one() || power == 0 {
+                write!(f, "{magnitude}")?;
+            }
+            match power {
+                0 => (),
+                1 => write!(f, "x")?,
+                power => write!(f, "x^{power}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::{BigInt, BigRational};
+
+    use crate::History;
+
+    #[test]
+    fn polynomial() {
+        let history = History::parse("1 3 6 10 15 21").unwrap();
+        let polynomial = history.polynomial();
+        assert_eq!(polynomial.to_string(), "1/2x^2 + 3/2x + 1");
+        assert_eq!(polynomial.degree(), Some(2));
+
+        let history = History::parse("10 13 16 21 30 45").unwrap();
+        assert_eq!(
+            history.polynomial().to_string(),
+            "1/3x^3 - x^2 + 11/3x + 10"
+        );
+
+        let history = History::parse("5 2 -1").unwrap();
+        assert_eq!(history.polynomial().to_string(), "-3x + 5");
+        let history = History::parse("0 0 0").unwrap();
+        assert_eq!(history.polynomial().to_string(), "0");
+        assert_eq!(history.polynomial().degree(), None);
+    }
+
+    #[test]
+    fn eval() {
+        let history = History::parse("10 13 16 21 30 45").unwrap();
+        let polynomial = history.polynomial();
+        for index in -20..20 {
+            let index = BigInt::from(index);
+            assert_eq!(
+                polynomial.eval(&BigRational::from(index.clone())),
+                BigRational::from(history.value_at(&index))
+            );
+        }
+        let half = BigRational::new(1.into(), 2.into());
+        assert_eq!(
+            polynomial.eval(&half),
+            BigRational::new(93.into(), 8.into())
+        );
+    }
+}